@@ -0,0 +1,84 @@
+//!
+//! # Dictionary Benchmarks
+//!
+//! Load time of the bundled `en_us` word list, and lookup latency of words
+//! in different cases
+//!
+//! Suggestion latency isn't measured, y3 doesn't suggest corrections yet. A
+//! `dictionary/suggest` group belongs here once it does.
+//!
+//! Run with `cargo bench --bench dictionary`
+//!
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use std::path::Path;
+use y3::{dictionary::Dictionary, tokenizer::Tokenizer};
+
+///
+/// Words of this crate's own code, known and unknown ones in their original case
+///
+const CODE: &str = include_str!("../src/tokenizer.rs");
+
+fn bench_load(c: &mut Criterion) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("dictionaries/en_us.txt");
+
+    let mut group = c.benchmark_group("dictionary/load");
+
+    group.bench_function("bundled", |b| b.iter(Dictionary::en_us));
+
+    group.bench_function("file", |b| {
+        b.iter(|| {
+            let mut dictionary = Dictionary::new();
+            dictionary.load(black_box(&path)).unwrap();
+
+            dictionary
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_lookup(c: &mut Criterion) {
+    let dictionary = Dictionary::en_us();
+
+    let tokenizer = Tokenizer::new();
+    let words: Vec<&str> = tokenizer.iter_str(CODE).map(|token| token.word()).collect();
+
+    let lowercase: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+    let uppercase: Vec<String> = words.iter().map(|word| word.to_uppercase()).collect();
+
+    let mut group = c.benchmark_group("dictionary/lookup");
+    group.throughput(Throughput::Elements(words.len() as u64));
+
+    group.bench_function("mixed_case", |b| {
+        b.iter(|| {
+            words
+                .iter()
+                .filter(|word| dictionary.contains(black_box(word)))
+                .count()
+        })
+    });
+
+    group.bench_function("lowercase", |b| {
+        b.iter(|| {
+            lowercase
+                .iter()
+                .filter(|word| dictionary.contains(black_box(word)))
+                .count()
+        })
+    });
+
+    group.bench_function("uppercase", |b| {
+        b.iter(|| {
+            uppercase
+                .iter()
+                .filter(|word| dictionary.contains(black_box(word)))
+                .count()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_load, bench_lookup);
+criterion_main!(benches);
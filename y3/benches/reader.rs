@@ -0,0 +1,90 @@
+//!
+//! # Reader Benchmarks
+//!
+//! Walks a synthetic deep tree of directories with [Reader::get_files], serially
+//! and in parallel, with and without ignore files
+//!
+//! Run with `cargo bench --bench reader`
+//!
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process, thread,
+};
+use y3::reader::Reader;
+
+///
+/// Levels of nested directories below the root
+///
+const DEPTH: usize = 6;
+
+///
+/// Sub directories in each directory
+///
+const FANOUT: usize = 3;
+
+///
+/// Files in each directory
+///
+const FILES_PER_DIR: usize = 4;
+
+///
+/// Create the tree below [dir], returns the number of files which aren't ignored
+///
+fn create_tree(dir: &Path, depth: usize) -> usize {
+    fs::create_dir_all(dir).unwrap();
+
+    for i in 0..FILES_PER_DIR {
+        fs::write(dir.join(format!("file_{i}.txt")), "hello world").unwrap();
+    }
+    fs::write(dir.join("debug.log"), "ignored").unwrap();
+
+    let mut files = FILES_PER_DIR;
+
+    if depth > 0 {
+        for i in 0..FANOUT {
+            files += create_tree(&dir.join(format!("dir_{i}")), depth - 1);
+        }
+    }
+
+    files
+}
+
+fn bench_get_files(c: &mut Criterion) {
+    let root: PathBuf = env::temp_dir().join(format!("y3_bench_tree_{}", process::id()));
+    let files = create_tree(&root, DEPTH);
+    fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+
+    let parallel = thread::available_parallelism().map_or(1, usize::from);
+
+    let mut group = c.benchmark_group("reader/get_files");
+    group.throughput(Throughput::Elements(files as u64));
+
+    for (name, jobs, ignore) in [
+        ("serial", 1, false),
+        ("parallel", parallel, false),
+        ("serial_gitignore", 1, true),
+        ("parallel_gitignore", parallel, true),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut reader = Reader::new(&root);
+                reader.set_jobs(jobs);
+
+                if ignore {
+                    reader.load_gitignore().unwrap();
+                }
+
+                reader.get_files(&root).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+    fs::remove_dir_all(&root).unwrap();
+}
+
+criterion_group!(benches, bench_get_files);
+criterion_main!(benches);
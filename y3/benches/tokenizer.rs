@@ -0,0 +1,103 @@
+//!
+//! # Tokenizer Benchmarks
+//!
+//! Tokenizes large prose and code corpora from a file with [Tokenizer::tokenize],
+//! and from memory collecting owned [Token]'s with [Tokenizer::tokenize_str]
+//! against borrowing [TokenRef]'s with [Tokenizer::iter_str]
+//!
+//! Run with `cargo bench --bench tokenizer`
+//!
+//! [Token]: y3::tokenizer::Token
+//! [TokenRef]: y3::tokenizer::TokenRef
+//!
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use std::{env, fs, process};
+use y3::tokenizer::Tokenizer;
+
+///
+/// Size of each corpus, roughly
+///
+const CORPUS_SIZE: usize = 1024 * 1024;
+
+///
+/// Prose, written like docs and READMEs
+///
+const PROSE: &str = "\
+# Getting Started
+
+The quick brown fox jumps over the lazy dog, while the spell checker keeps an eye
+on typos. Sometimes a mispeled word sneaks into the README, and nobody notices it
+for weeks. Reviewers are busy, and \"it's just docs\" is what everybody says until
+a customer files an issue about it (see https://example.com/issues/42).
+
+- Install the binary with `cargo install y3`
+- Run it on your repository, e.g. `y3 src docs`
+- Add unknown words to `y3.toml`, or fix them!
+";
+
+///
+/// Code of this crate, a realistic mix of identifiers, comments and strings
+///
+const CODE: &[&str] = &[
+    include_str!("../src/checker.rs"),
+    include_str!("../src/config.rs"),
+    include_str!("../src/reader.rs"),
+    include_str!("../src/tokenizer.rs"),
+];
+
+///
+/// Repeat the [parts] until the corpus is about [CORPUS_SIZE] bytes
+///
+fn corpus(parts: &[&str]) -> String {
+    let part = parts.concat();
+
+    part.repeat(CORPUS_SIZE.div_ceil(part.len()))
+}
+
+fn bench_corpus(c: &mut Criterion, name: &str, content: &str) {
+    let mut group = c.benchmark_group(format!("tokenize/{name}"));
+    group.throughput(Throughput::Bytes(content.len() as u64));
+
+    let path = env::temp_dir().join(format!("y3_bench_{name}_{}.txt", process::id()));
+    fs::write(&path, content).unwrap();
+
+    group.bench_function("file", |b| {
+        let mut tokenizer = Tokenizer::new();
+
+        b.iter(|| {
+            tokenizer.clear_tokens();
+            tokenizer.tokenize(black_box(&path)).unwrap();
+
+            tokenizer.tokens().len()
+        })
+    });
+
+    group.bench_function("owned", |b| {
+        let mut tokenizer = Tokenizer::new();
+
+        b.iter(|| {
+            tokenizer.clear_tokens();
+            tokenizer.tokenize_str(black_box(content));
+
+            tokenizer.tokens().len()
+        })
+    });
+
+    group.bench_function("borrowed", |b| {
+        let tokenizer = Tokenizer::new();
+
+        b.iter(|| tokenizer.iter_str(black_box(content)).count())
+    });
+
+    group.finish();
+    fs::remove_file(&path).unwrap();
+}
+
+fn bench_tokenize(c: &mut Criterion) {
+    bench_corpus(c, "prose", &corpus(&[PROSE]));
+    bench_corpus(c, "code", &corpus(CODE));
+}
+
+criterion_group!(benches, bench_tokenize);
+criterion_main!(benches);
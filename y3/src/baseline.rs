@@ -0,0 +1,367 @@
+//!
+//! # Baseline
+//!
+//! A snapshot of existing [Misspelling]'s, used to only report new ones
+//!
+//! ## Working
+//!
+//! - Each [Misspelling] is identified by its file path, word and a hash of the line
+//!   containing it
+//! - Findings present in the baseline are suppressed, every other one is reported
+//!
+//! ## Format
+//!
+//! Baselines are plain text files with one finding per line, sorted to keep diffs
+//! of the file small,
+//!
+//! ```text
+//! <context hash>\t<word>\t<path>
+//! ```
+//!
+//! ## Considerations
+//!
+//! - Findings are keyed by content rather than [Position::line_no], so adding or
+//!   removing lines around a finding does not invalidate the baseline
+//! - Editing the line containing a finding reports it again
+//! - Repeated findings are counted, so a second identical typo is still reported
+//! - Paths are relative to the directory of the baseline file, so it matches no
+//!   matter which directory y3 runs in and how the checked paths are spelled (e.g.
+//!   `.` or `/abs/repo`)
+//!
+//! [Position::line_no]: crate::tokenizer::Position::line_no
+//!
+
+use crate::checker::Misspelling;
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+};
+
+///
+/// Header written at the top of baseline files
+///
+const HEADER: &str = "# y3 baseline, generated with `y3 --write-baseline`";
+
+///
+/// Content based identity of a [Misspelling]
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Fingerprint {
+    ///
+    /// Hash of the line containing the misspelled word
+    ///
+    context: u64,
+
+    ///
+    /// The misspelled word
+    ///
+    word: String,
+
+    ///
+    /// Normalized path of the file containing the misspelled word, relative to
+    /// the root of the [Baseline] when it's inside of it
+    ///
+    path: String,
+}
+
+impl Fingerprint {
+    ///
+    /// Create the [Fingerprint] of a [Misspelling], with its path relative to
+    /// the [root]
+    ///
+    fn new(misspelling: &Misspelling, root: Option<&Path>) -> Self {
+        let path = misspelling.path();
+        let path = match root {
+            Some(root) => {
+                let path = Self::absolute_path(path);
+
+                match path.strip_prefix(root) {
+                    Ok(relative) => relative.to_path_buf(),
+                    Err(_) => path,
+                }
+            }
+            None => path.to_path_buf(),
+        };
+
+        Self {
+            context: Self::hash(misspelling.context()),
+            word: misspelling.word().to_string(),
+            path: Self::normalize_path(&path),
+        }
+    }
+
+    ///
+    /// Resolve the [path] against the current directory, following symlinks when
+    /// it exists
+    ///
+    fn absolute_path(path: &Path) -> PathBuf {
+        if let Ok(path) = path.canonicalize() {
+            return path;
+        }
+
+        // Paths which don't exist, e.g. given with `--stdin-filename`, are
+        // resolved without touching the file system
+        let path = env::current_dir().unwrap_or_default().join(path);
+        let mut absolute = PathBuf::new();
+
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    absolute.pop();
+                }
+                component => absolute.push(component),
+            }
+        }
+
+        absolute
+    }
+
+    ///
+    /// Hash [content] using 64-bit FNV-1a
+    ///
+    /// A fixed hash function is used (unlike [std::hash::DefaultHasher]) so
+    /// baselines stay valid across Rust releases.
+    ///
+    fn hash(content: &str) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        content.bytes().fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        })
+    }
+
+    ///
+    /// Remove `.` components and use `/` as the separator, so `./src/lib.rs`
+    /// and `src/lib.rs` share the same fingerprint on every platform
+    ///
+    fn normalize_path(path: &Path) -> String {
+        path.components()
+            .filter(|c| !matches!(c, Component::CurDir))
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    ///
+    /// Parse a `<context hash>\t<word>\t<path>` line of the baseline file
+    ///
+    fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.splitn(3, '\t');
+
+        let context = u64::from_str_radix(parts.next()?, 16).ok()?;
+        let word = parts.next()?.to_string();
+        let path = parts.next()?.to_string();
+
+        Some(Self {
+            context,
+            word,
+            path,
+        })
+    }
+}
+
+///
+/// Snapshot of [Misspelling]'s to be suppressed
+///
+#[derive(Debug, Default)]
+pub struct Baseline {
+    ///
+    /// Number of occurrences of each finding
+    ///
+    entries: HashMap<Fingerprint, usize>,
+
+    ///
+    /// Canonical directory of the baseline file, paths of findings are relative
+    /// to it. Paths are used as given when `None`.
+    ///
+    root: Option<PathBuf>,
+}
+
+impl Baseline {
+    ///
+    /// Create an empty instance of [Baseline]
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Create an empty instance of [Baseline] to be saved to the file at [path]
+    ///
+    /// Paths of findings are relative to the directory of the file, which has to
+    /// exist.
+    ///
+    pub fn for_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let dir = match path.as_ref().parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        Ok(Self {
+            entries: HashMap::new(),
+            root: Some(dir.canonicalize()?),
+        })
+    }
+
+    ///
+    /// Load a baseline from the file at [path]
+    ///
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let content = fs::read_to_string(&path)?;
+        let mut baseline = Self::for_file(&path)?;
+
+        for (line_no, line) in content.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fingerprint = Fingerprint::parse(line).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid baseline entry at line {}", line_no + 1),
+                )
+            })?;
+
+            *baseline.entries.entry(fingerprint).or_default() += 1;
+        }
+
+        Ok(baseline)
+    }
+
+    ///
+    /// Write the baseline to the file at [path]
+    ///
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut entries: Vec<(&Fingerprint, &usize)> = self.entries.iter().collect();
+        entries.sort();
+
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        writeln!(file, "{HEADER}")?;
+
+        for (fingerprint, count) in entries {
+            for _ in 0..*count {
+                writeln!(
+                    file,
+                    "{:016x}\t{}\t{}",
+                    fingerprint.context, fingerprint.word, fingerprint.path
+                )?;
+            }
+        }
+
+        file.flush()
+    }
+
+    ///
+    /// Add the [misspelling] to the baseline
+    ///
+    pub fn insert(&mut self, misspelling: &Misspelling) {
+        *self
+            .entries
+            .entry(Fingerprint::new(misspelling, self.root.as_deref()))
+            .or_default() += 1;
+    }
+
+    ///
+    /// Check if the [misspelling] is part of the baseline
+    ///
+    /// Each baseline entry suppresses a single occurrence, so it's consumed once
+    /// matched.
+    ///
+    pub fn suppress(&mut self, misspelling: &Misspelling) -> bool {
+        let fingerprint = Fingerprint::new(misspelling, self.root.as_deref());
+
+        match self.entries.get_mut(&fingerprint) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    ///
+    /// Total number of findings in the baseline
+    ///
+    pub fn len(&self) -> usize {
+        self.entries.values().sum()
+    }
+
+    ///
+    /// Check if the baseline has no findings
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::Token;
+
+    fn misspelling(path: &str, word: &str, line_no: usize, context: &str) -> Misspelling {
+        Misspelling::new(path, Token::new(word, 0, word.len() - 1, line_no), context)
+    }
+
+    #[test]
+    fn test_suppress_ignores_line_shifts() {
+        let mut baseline = Baseline::new();
+        baseline.insert(&misspelling("./notes.txt", "tpyo", 2, "old tpyo here"));
+
+        // Same line moved down and path spelled differently
+        assert!(baseline.suppress(&misspelling("notes.txt", "tpyo", 10, "  old tpyo here")));
+
+        // Edited line is reported again
+        let mut baseline = Baseline::new();
+        baseline.insert(&misspelling("notes.txt", "tpyo", 2, "old tpyo here"));
+        assert!(!baseline.suppress(&misspelling("notes.txt", "tpyo", 2, "new tpyo here")));
+    }
+
+    #[test]
+    fn test_suppress_counts_occurrences() {
+        let mut baseline = Baseline::new();
+        baseline.insert(&misspelling("notes.txt", "tpyo", 1, "tpyo"));
+
+        assert!(baseline.suppress(&misspelling("notes.txt", "tpyo", 1, "tpyo")));
+        assert!(!baseline.suppress(&misspelling("notes.txt", "tpyo", 2, "tpyo")));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = env::temp_dir().join(format!("y3_baseline_{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        let file_path = dir.join("baseline.txt");
+
+        let a = dir.join("a.txt");
+        let b = dir.join("src/b.txt");
+        fs::write(&a, "tpyo").unwrap();
+        fs::write(&b, "a mistkae").unwrap();
+
+        let mut baseline = Baseline::for_file(&file_path).unwrap();
+        baseline.insert(&misspelling(a.to_str().unwrap(), "tpyo", 1, "tpyo"));
+        baseline.insert(&misspelling(a.to_str().unwrap(), "tpyo", 2, "tpyo"));
+        baseline.insert(&misspelling(b.to_str().unwrap(), "mistkae", 1, "a mistkae"));
+        baseline.save(&file_path).unwrap();
+
+        let content = fs::read_to_string(&file_path).unwrap();
+        let mut loaded = Baseline::load(&file_path).unwrap();
+
+        // The same files spelled differently, e.g. checked from another directory
+        let b = dir.join("src/../src/./b.txt");
+        let found = [
+            loaded.suppress(&misspelling(b.to_str().unwrap(), "mistkae", 5, "a mistkae")),
+            loaded.suppress(&misspelling(a.to_str().unwrap(), "tpyo", 1, "tpyo")),
+            loaded.suppress(&misspelling(a.to_str().unwrap(), "tpyo", 1, "tpyo")),
+        ];
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(content.lines().any(|line| line.ends_with("\tsrc/b.txt")));
+        assert!(content.lines().any(|line| line.ends_with("\ta.txt")));
+        assert_eq!(found, [true; 3]);
+        assert!(loaded.is_empty());
+    }
+}
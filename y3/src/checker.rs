@@ -0,0 +1,633 @@
+//!
+//! # Checker
+//!
+//! Spell checks files by running the [Tokenizer] over them and looking up every
+//! [Token] in a [Dictionary]
+//!
+//! ## Considerations
+//!
+//! - The [Dictionary] is borrowed, so it can be shared between many checkers
+//! - Each [Checker] owns its [Tokenizer], words are looked up while borrowed from
+//!   the file content and only [Misspelling]'s own a copy
+//! - [Checker::check_files] runs a [Checker] per thread, all of them sharing the
+//!   same read-only [Dictionary]'s
+//! - [Checker::check_stream] does the same for files which are still being found,
+//!   so checking starts with the first file of a walk instead of after the last
+//! - Binary files are skipped, see [encoding] for how text is detected and decoded
+//! - Hyphenated compounds are known when the [Dictionary] has them with or without
+//!   hyphens (e.g. `e-mail`, `exam-ple` broken across lines), otherwise each of
+//!   their unknown words is a [Misspelling]
+//! - Mixed case words split by the [Tokenizer] (e.g. `iPhone`) are known when the
+//!   [Dictionary] has them as they are
+//! - Lowercase identifiers made of known words (e.g. `eventloop`) can be accepted,
+//!   see [Checker::set_compound_words]
+//!
+
+use crate::{
+    dictionary::Dictionary,
+    encoding::{self, Encoding},
+    tokenizer::{Position, Token, TokenRef, Tokenizer, HYPHENS},
+};
+use std::{
+    fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+    sync::{mpsc, Mutex},
+    thread,
+};
+
+///
+/// A [Token] which is not known to the [Dictionary]
+///
+#[derive(Debug)]
+pub struct Misspelling {
+    ///
+    /// Path of the file the [Token] was parsed from
+    ///
+    path: PathBuf,
+
+    ///
+    /// The misspelled [Token]
+    ///
+    token: Token,
+
+    ///
+    /// Content of the line containing the [Token], trimmed of surrounding whitespace
+    ///
+    context: String,
+}
+
+impl Misspelling {
+    ///
+    /// Create a new instance of [Misspelling]
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file containing the misspelled token
+    /// * `token` - The misspelled [Token]
+    /// * `context` - Content of the line containing the token
+    ///
+    pub fn new<P: AsRef<Path>>(path: P, token: Token, context: &str) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            token,
+            context: context.trim().to_string(),
+        }
+    }
+
+    ///
+    /// Getter to read the [path] of the file containing the misspelling
+    ///
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    ///
+    /// Getter to read the misspelled `word`
+    ///
+    pub fn word(&self) -> &str {
+        self.token.word()
+    }
+
+    ///
+    /// Getter to read the [Position] of the misspelled word
+    ///
+    pub fn position(&self) -> &Position {
+        self.token.position()
+    }
+
+    ///
+    /// Getter to read the content of the line containing the misspelled word
+    ///
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+}
+
+///
+/// Spell checker for files, backed by a shared [Dictionary]
+///
+#[derive(Debug, Clone)]
+pub struct Checker<'a> {
+    ///
+    /// Dictionary of known words
+    ///
+    dictionary: &'a Dictionary,
+
+    ///
+    /// Tokenizer used to parse words from files
+    ///
+    tokenizer: Tokenizer,
+
+    ///
+    /// Encoding of checked files, detected when `None`
+    ///
+    encoding: Option<Encoding>,
+
+    ///
+    /// Minimum length of each word of a segmented compound, compounds aren't
+    /// segmented when `None`
+    ///
+    compound_min_part_length: Option<usize>,
+}
+
+impl<'a> Checker<'a> {
+    ///
+    /// Create an instance of [Checker] backed by the [dictionary]
+    ///
+    pub fn new(dictionary: &'a Dictionary) -> Self {
+        Self::with_tokenizer(dictionary, Tokenizer::new())
+    }
+
+    ///
+    /// Create an instance of [Checker] backed by the [dictionary], using a
+    /// custom configured [tokenizer]
+    ///
+    pub fn with_tokenizer(dictionary: &'a Dictionary, tokenizer: Tokenizer) -> Self {
+        Self {
+            dictionary,
+            tokenizer,
+            encoding: None,
+            compound_min_part_length: None,
+        }
+    }
+
+    ///
+    /// Use the [dictionary] for upcoming checks, e.g. when files are written
+    /// in a different language
+    ///
+    pub fn set_dictionary(&mut self, dictionary: &'a Dictionary) {
+        self.dictionary = dictionary;
+    }
+
+    ///
+    /// Decode upcoming files with the [encoding] instead of detecting it
+    ///
+    pub fn set_encoding(&mut self, encoding: Option<Encoding>) {
+        self.encoding = encoding;
+    }
+
+    ///
+    /// Accept lowercase words which can be segmented into 2 or 3 known words of
+    /// at least [min_part_length] characters (e.g. `filesystem`), disabled by
+    /// default
+    ///
+    /// Compounds aren't segmented when `None`, see [Dictionary::segment].
+    /// Misspelled compounds are reported as a whole, without suggestions for
+    /// their words.
+    ///
+    pub fn set_compound_words(&mut self, min_part_length: Option<usize>) {
+        self.compound_min_part_length = min_part_length;
+    }
+
+    ///
+    /// Check if the [word] is known to the [Dictionary], on its own or as a
+    /// compound of known words
+    ///
+    fn is_known(&self, word: &str) -> bool {
+        if self.dictionary.contains(word) {
+            return true;
+        }
+
+        self.compound_min_part_length
+            .is_some_and(|len| self.dictionary.segment(word, len).is_some())
+    }
+
+    ///
+    /// Spell check the files at [paths] using [jobs] threads, each running its own
+    /// clone of this [Checker]
+    ///
+    /// Results are returned in the same order as [paths], regardless of which
+    /// thread checked the file. Same as [Checker::check_stream] for a known list
+    /// of files.
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - Paths of the files to check
+    /// * `jobs` - Number of threads, values below `1` are treated as `1`
+    /// * `configure` - Called before checking each file, e.g. to select the
+    ///   [Dictionary] for the language of the file
+    ///
+    pub fn check_files<P, F>(
+        &self,
+        paths: &[P],
+        jobs: usize,
+        configure: F,
+    ) -> Vec<io::Result<Vec<Misspelling>>>
+    where
+        P: AsRef<Path>,
+        F: Fn(&Path, &mut Checker<'a>) + Sync,
+    {
+        let walk = |found: &mut dyn FnMut(PathBuf)| {
+            for path in paths {
+                found(path.as_ref().to_path_buf());
+            }
+
+            Ok(())
+        };
+
+        // Passing the paths doesn't fail, so the results are always there
+        self.check_stream(jobs, walk, configure)
+            .into_iter()
+            .flatten()
+            .map(|(_, result)| result)
+            .collect()
+    }
+
+    ///
+    /// Spell check the files passed by [walk] using [jobs] threads while [walk]
+    /// is still looking for more, each thread running its own clone of this [Checker]
+    ///
+    /// [walk] runs on the calling thread and passes every file to the callback it
+    /// receives. Results are returned with their paths in the order the files
+    /// were passed, regardless of which thread checked the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `jobs` - Number of threads, values below `1` are treated as `1`
+    /// * `walk` - Finds the files to check, e.g. with [crate::reader::Reader::for_each_file]
+    /// * `configure` - Called before checking each file, e.g. to select the
+    ///   [Dictionary] for the language of the file
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of [walk], files passed before it failed are still
+    /// checked but their results are dropped
+    ///
+    pub fn check_stream<W, F>(
+        &self,
+        jobs: usize,
+        walk: W,
+        configure: F,
+    ) -> io::Result<Vec<(PathBuf, io::Result<Vec<Misspelling>>)>>
+    where
+        W: FnOnce(&mut dyn FnMut(PathBuf)) -> io::Result<()>,
+        F: Fn(&Path, &mut Checker<'a>) + Sync,
+    {
+        let (sender, receiver) = mpsc::channel::<(usize, PathBuf)>();
+        let receiver = Mutex::new(receiver);
+
+        let worker = || {
+            let mut checker = self.clone();
+            let mut results = Vec::new();
+
+            // Files are claimed one at a time, the channel closes once [walk] is done
+            loop {
+                let next = receiver
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .recv();
+                let Ok((idx, path)) = next else {
+                    break;
+                };
+
+                configure(&path, &mut checker);
+                let result = checker.check_file(&path);
+                results.push((idx, path, result));
+            }
+
+            results
+        };
+
+        let (walked, mut results) = thread::scope(|scope| {
+            let handles: Vec<_> = (0..jobs.max(1)).map(|_| scope.spawn(worker)).collect();
+
+            let mut count = 0;
+            let walked = walk(&mut |path| {
+                // Only fails when all workers panicked, which is raised on join
+                let _ = sender.send((count, path));
+                count += 1;
+            });
+            drop(sender);
+
+            let results: Vec<_> = handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect();
+
+            (walked, results)
+        });
+        walked?;
+
+        results.sort_by_key(|(idx, _, _)| *idx);
+        Ok(results
+            .into_iter()
+            .map(|(_, path, result)| (path, result))
+            .collect())
+    }
+
+    ///
+    /// Spell check the file at [file_path] and return all [Misspelling]'s
+    ///
+    /// Binary files don't have any [Misspelling]'s.
+    ///
+    pub fn check_file<P: AsRef<Path>>(&mut self, file_path: P) -> io::Result<Vec<Misspelling>> {
+        let bytes = fs::read(&file_path)?;
+
+        Ok(self.check_bytes(bytes, file_path.as_ref()))
+    }
+
+    ///
+    /// Spell check the content of [reader], e.g. stdin, and return all [Misspelling]'s
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Content to check
+    /// * `file_path` - Path reported for [Misspelling]'s, the content doesn't
+    ///   need to be read from it
+    ///
+    pub fn check_reader<R: BufRead, P: AsRef<Path>>(
+        &mut self,
+        mut reader: R,
+        file_path: P,
+    ) -> io::Result<Vec<Misspelling>> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        Ok(self.check_bytes(bytes, file_path.as_ref()))
+    }
+
+    ///
+    /// Spell check the raw [bytes] of a file, decoded with the configured encoding
+    ///
+    fn check_bytes(&mut self, bytes: Vec<u8>, file_path: &Path) -> Vec<Misspelling> {
+        let Some(content) = encoding::decode_owned(bytes, self.encoding) else {
+            return Vec::new();
+        };
+
+        // Words are borrowed from the content, only misspelled ones are copied
+        let mut tokens: Vec<Token> = Vec::new();
+
+        for token in self.tokenizer.iter_str(&content) {
+            // Mixed case words like `iPhone` are split by the tokenizer, but may be
+            // known as they are
+            if token.unsplit() != token.word() && self.dictionary.contains(token.unsplit()) {
+                continue;
+            }
+
+            if !token.is_compound() {
+                if !self.is_known(token.word()) {
+                    tokens.push(Token::from(token));
+                }
+
+                continue;
+            }
+
+            if self.dictionary.contains(token.text())
+                || self.dictionary.contains(&token.text().replace(HYPHENS, ""))
+            {
+                continue;
+            }
+
+            // Unknown compounds fall back to their words
+            tokens.extend(
+                token
+                    .parts()
+                    .iter()
+                    .filter(|part| !self.is_known(part.word()))
+                    .map(TokenRef::to_token),
+            );
+        }
+
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let lines: Vec<&str> = content.lines().collect();
+
+        tokens
+            .into_iter()
+            .map(|token| {
+                let context = lines
+                    .get(token.position().line_no() - 1)
+                    .copied()
+                    .unwrap_or_default();
+
+                Misspelling::new(file_path, token, context)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::CaseSensitivity;
+    use std::{env, fs};
+
+    #[test]
+    fn test_check_files_keeps_order() {
+        let dir = env::temp_dir().join(format!("y3_checker_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let paths: Vec<PathBuf> = (0..20)
+            .map(|i| {
+                let path = dir.join(format!("{i}.txt"));
+                fs::write(&path, format!("hello wrld{i}x")).unwrap();
+                path
+            })
+            .collect();
+
+        let dictionary = Dictionary::en_us();
+        let results = Checker::new(&dictionary).check_files(&paths, 4, |_, _| {});
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(results.len(), paths.len());
+
+        for (path, result) in paths.iter().zip(results) {
+            let misspellings = result.unwrap();
+
+            assert_eq!(misspellings.len(), 1);
+            assert_eq!(misspellings[0].path(), path);
+        }
+    }
+
+    #[test]
+    fn test_check_stream() {
+        let dir = env::temp_dir().join(format!("y3_checker_stream_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let paths: Vec<PathBuf> = (0..20)
+            .map(|i| {
+                let path = dir.join(format!("{i}.txt"));
+                fs::write(&path, format!("hello wrld{i}x")).unwrap();
+                path
+            })
+            .collect();
+
+        let dictionary = Dictionary::en_us();
+        let checker = Checker::new(&dictionary);
+
+        let results = checker
+            .check_stream(
+                4,
+                |found| {
+                    paths.iter().cloned().for_each(found);
+                    Ok(())
+                },
+                |_, _| {},
+            )
+            .unwrap();
+
+        let failed = checker.check_stream(
+            4,
+            |found| {
+                found(paths[0].clone());
+                Err(io::Error::other("walk failed"))
+            },
+            |_, _| {},
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(results.len(), paths.len());
+
+        for (path, (checked_path, result)) in paths.iter().zip(results) {
+            let misspellings = result.unwrap();
+
+            assert_eq!(&checked_path, path);
+            assert_eq!(misspellings.len(), 1);
+            assert_eq!(misspellings[0].path(), path);
+        }
+
+        assert!(failed.is_err());
+    }
+
+    #[test]
+    fn test_check_file_encodings() {
+        let dir = env::temp_dir().join(format!("y3_checker_encodings_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let binary = dir.join("image.png");
+        fs::write(&binary, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR wrld").unwrap();
+
+        let latin1 = dir.join("latin1.txt");
+        fs::write(&latin1, b"caf\xe9 wrld").unwrap();
+
+        let utf16 = dir.join("utf16.txt");
+        let content: Vec<u8> = "hello wrld"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        fs::write(&utf16, content).unwrap();
+
+        let dictionary = Dictionary::en_us();
+        let mut checker = Checker::new(&dictionary);
+
+        let binary_result = checker.check_file(&binary).unwrap();
+        let latin1_result = checker.check_file(&latin1).unwrap();
+
+        // UTF-16 without a byte order mark has to be configured
+        let detected_utf16 = checker.check_file(&utf16).unwrap();
+        checker.set_encoding(Some(Encoding::Utf16Le));
+        let utf16_result = checker.check_file(&utf16).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(binary_result.is_empty());
+        // The bundled dictionary has no accented words
+        let latin1_words: Vec<&str> = latin1_result.iter().map(Misspelling::word).collect();
+        assert_eq!(latin1_words, ["café", "wrld"]);
+        assert_eq!(latin1_result[0].context(), "café wrld");
+        assert!(detected_utf16.is_empty());
+        assert_eq!(utf16_result.len(), 1);
+        assert_eq!(utf16_result[0].word(), "wrld");
+    }
+
+    #[test]
+    fn test_check_reader() {
+        let dictionary = Dictionary::en_us();
+        let mut checker = Checker::new(&dictionary);
+
+        let misspellings = checker
+            .check_reader(&b"hello\nsome wrld here\n"[..], "<stdin>")
+            .unwrap();
+
+        assert_eq!(misspellings.len(), 1);
+        assert_eq!(misspellings[0].path(), Path::new("<stdin>"));
+        assert_eq!(misspellings[0].position().line_no(), 2);
+        assert_eq!(misspellings[0].context(), "some wrld here");
+    }
+
+    #[test]
+    fn test_check_hyphenated_compounds() {
+        let dictionary = Dictionary::en_us();
+        let tokenizer = Tokenizer::builder().hyphenated_compounds(true).build();
+        let mut checker = Checker::with_tokenizer(&dictionary, tokenizer);
+
+        let content = "send an e-mail to re-enable the well-wrld exam-\nple, re-enbale it\n";
+        let misspellings = checker.check_reader(content.as_bytes(), "<stdin>").unwrap();
+        let words: Vec<&str> = misspellings.iter().map(|m| m.word()).collect();
+
+        assert_eq!(words, ["wrld", "enbale"]);
+        assert_eq!(misspellings[1].position().line_no(), 2);
+        assert_eq!(misspellings[1].position().column(), 8);
+    }
+
+    #[test]
+    fn test_check_case_sensitivity() {
+        let mut dictionary = Dictionary::new();
+        dictionary.extend_from_str("the\nand\non\nEnglish\nGitHub\niPhone\nmacOS\nids\n");
+
+        let content = "The english Github, GitHub and ENGLISH\niPhone IPhone on macOS MacOS IDs\n";
+        let misspellings = Checker::new(&dictionary)
+            .check_reader(content.as_bytes(), "<stdin>")
+            .unwrap();
+        let words: Vec<&str> = misspellings.iter().map(|m| m.word()).collect();
+        assert_eq!(words, ["english", "Github", "IPhone", "Mac", "OS"]);
+
+        dictionary.set_case_sensitivity(CaseSensitivity::Insensitive);
+        let misspellings = Checker::new(&dictionary)
+            .check_reader(content.as_bytes(), "<stdin>")
+            .unwrap();
+        assert!(misspellings.is_empty());
+    }
+
+    #[test]
+    fn test_check_compound_words() {
+        let dictionary = Dictionary::en_us();
+        let mut checker = Checker::new(&dictionary);
+        let content = "the eventloop reads the filesystem, not the fileio\n";
+
+        // Compounds aren't segmented by default
+        let misspellings = checker.check_reader(content.as_bytes(), "<stdin>").unwrap();
+        assert_eq!(misspellings.len(), 3);
+
+        checker.set_compound_words(Some(3));
+        let misspellings = checker.check_reader(content.as_bytes(), "<stdin>").unwrap();
+        let words: Vec<&str> = misspellings.iter().map(|m| m.word()).collect();
+        assert_eq!(words, ["fileio"]);
+
+        checker.set_compound_words(Some(5));
+        let misspellings = checker.check_reader(content.as_bytes(), "<stdin>").unwrap();
+        let words: Vec<&str> = misspellings.iter().map(|m| m.word()).collect();
+        assert_eq!(words, ["eventloop", "filesystem", "fileio"]);
+
+        checker.set_compound_words(None);
+        let misspellings = checker.check_reader(content.as_bytes(), "<stdin>").unwrap();
+        assert_eq!(misspellings.len(), 3);
+    }
+
+    #[test]
+    fn test_check_compound_words_typos() {
+        let dictionary = Dictionary::en_us();
+        let mut checker = Checker::new(&dictionary);
+        checker.set_compound_words(Some(3));
+
+        // Each of them splits into known words, e.g. `begin` + `ing` or `ret` + `run`
+        let typos = [
+            "paramter",
+            "funtion",
+            "retrun",
+            "arguement",
+            "settting",
+            "proccess",
+            "begining",
+            "comming",
+        ];
+        let content = typos.join(" ");
+
+        let misspellings = checker.check_reader(content.as_bytes(), "<stdin>").unwrap();
+        let words: Vec<&str> = misspellings.iter().map(|m| m.word()).collect();
+        assert_eq!(words, typos);
+    }
+}
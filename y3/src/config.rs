@@ -0,0 +1,880 @@
+//!
+//! # Config
+//!
+//! Per project configuration, read from a `y3.toml` file or the `[tool.y3]` section
+//! of a `pyproject.toml` file
+//!
+//! ## Discovery
+//!
+//! Starting from the checked directory, every parent directory is searched for,
+//!
+//! - `y3.toml`
+//! - `pyproject.toml` with a `[tool.y3]` section
+//!
+//! The first file found is used, so configs are not merged.
+//!
+//! ## Example
+//!
+//! ```toml
+//! # Extra word lists for each language, relative to the config file
+//! dictionaries = { en_us = ["words.txt"], fr = ["dictionaries/fr.txt"] }
+//!
+//! # Language used for files not matched by [languages]
+//! default-language = "en_us"
+//!
+//! # Extra globs to be ignored, relative to the config file
+//! ignore = ["vendor/**", "**/*.lock"]
+//!
+//! # Extra regex patterns to be ignored by the tokenizer
+//! ignore-patterns = ['^[A-Z]+-\d+$']
+//!
+//! # Extra regex patterns to be ignored by the tokenizer, matched against entire lines
+//! line-ignore-patterns = ['Signed-off-by: .*']
+//!
+//! # Built-in patterns which should not be ignored, e.g. `url`, `file-path`, `number`,
+//! # `regex` or `email`
+//! disable-builtin-patterns = ["number"]
+//!
+//! # Skip random looking strings like git SHAs, UUIDs or API keys
+//! ignore-random-strings = true
+//!
+//! # Check hyphenated compounds like `e-mail` as a whole before their words, and
+//! # rejoin words broken across lines by a trailing hyphen
+//! hyphenated-compounds = false
+//!
+//! # Accept lowercase identifiers made of 2 or 3 known words like `filesystem`, each
+//! # at least `compound-min-part-length` characters long
+//! compound-words = false
+//! compound-min-part-length = 3
+//!
+//! # Words shorter than this (in characters) are not checked, defaults to 2
+//! min-word-length = 3
+//!
+//! # Words with digits like `utf8` are checked as they are (`keep`), with trailing
+//! # digits stripped (`strip`) or skipped (`skip`)
+//! digits = "keep"
+//!
+//! # Words with capitals in dictionaries keep their case (`sensitive`), e.g. `English`
+//! # doesn't match "english", or match in any case (`insensitive`)
+//! case-sensitivity = "sensitive"
+//!
+//! # Output format, `text` or `json`
+//! format = "text"
+//!
+//! # Severity of findings, `error` or `warning` (doesn't fail the run)
+//! severity = "error"
+//!
+//! # Files larger than this (in bytes) are skipped
+//! max-file-size = 1048576
+//!
+//! # Language of files matching a glob, first matching glob wins
+//! [languages]
+//! "docs/fr/**" = "fr"
+//!
+//! # Encoding of files matching a glob, `utf-8`, `utf-16le`, `utf-16be` or
+//! # `latin-1`, detected for other files
+//! [encodings]
+//! "legacy/**" = "latin-1"
+//! ```
+//!
+
+use crate::{
+    dictionary::CaseSensitivity,
+    encoding::Encoding,
+    tokenizer::{BuiltinPattern, Digits},
+};
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+///
+/// Name of the dedicated config file
+///
+const CONFIG_FILE: &str = "y3.toml";
+
+///
+/// Name of the python project file, which may contain a `[tool.y3]` section
+///
+const PYPROJECT_FILE: &str = "pyproject.toml";
+
+///
+/// Language of the bundled dictionary
+///
+pub const DEFAULT_LANGUAGE: &str = "en_us";
+
+///
+/// Format used to print findings
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    ///
+    /// `path:line:column: severity: word`
+    ///
+    #[default]
+    Text,
+
+    ///
+    /// One JSON object per finding
+    ///
+    Json,
+}
+
+///
+/// Severity of findings
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    ///
+    /// Findings fail the run
+    ///
+    #[default]
+    Error,
+
+    ///
+    /// Findings are reported but don't fail the run
+    ///
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+///
+/// Config as written in the config file
+///
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+struct RawConfig {
+    dictionaries: BTreeMap<String, Vec<PathBuf>>,
+    default_language: String,
+    ignore: Vec<String>,
+    #[serde(deserialize_with = "ordered_pairs")]
+    languages: Vec<(String, String)>,
+    ignore_patterns: Vec<String>,
+    line_ignore_patterns: Vec<String>,
+    disable_builtin_patterns: Vec<String>,
+    ignore_random_strings: bool,
+    hyphenated_compounds: bool,
+    compound_words: bool,
+    compound_min_part_length: usize,
+    min_word_length: Option<usize>,
+    digits: Option<String>,
+    case_sensitivity: Option<String>,
+    format: OutputFormat,
+    severity: Severity,
+    max_file_size: Option<u64>,
+    #[serde(deserialize_with = "ordered_pairs")]
+    encodings: Vec<(String, String)>,
+}
+
+impl Default for RawConfig {
+    fn default() -> Self {
+        Self {
+            dictionaries: BTreeMap::new(),
+            default_language: DEFAULT_LANGUAGE.to_string(),
+            ignore: Vec::new(),
+            languages: Vec::new(),
+            ignore_patterns: Vec::new(),
+            line_ignore_patterns: Vec::new(),
+            disable_builtin_patterns: Vec::new(),
+            ignore_random_strings: true,
+            hyphenated_compounds: false,
+            compound_words: false,
+            compound_min_part_length: 3,
+            min_word_length: None,
+            digits: None,
+            case_sensitivity: None,
+            format: OutputFormat::default(),
+            severity: Severity::default(),
+            max_file_size: None,
+            encodings: Vec::new(),
+        }
+    }
+}
+
+///
+/// Deserialize a table into key value pairs, keeping the order of the config file
+///
+fn ordered_pairs<'de, D>(deserializer: D) -> Result<Vec<(String, String)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct PairsVisitor;
+
+    impl<'de> Visitor<'de> for PairsVisitor {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a table of strings")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut pairs = Vec::new();
+
+            while let Some(pair) = map.next_entry()? {
+                pairs.push(pair);
+            }
+
+            Ok(pairs)
+        }
+    }
+
+    deserializer.deserialize_map(PairsVisitor)
+}
+
+///
+/// Project configuration for y3
+///
+#[derive(Debug)]
+pub struct Config {
+    ///
+    /// Directory containing the config file, relative paths and globs are
+    /// resolved against it
+    ///
+    root: PathBuf,
+
+    ///
+    /// Extra word lists for each language
+    ///
+    dictionaries: BTreeMap<String, Vec<PathBuf>>,
+
+    ///
+    /// Language used for files not matched by [Config::languages]
+    ///
+    default_language: String,
+
+    ///
+    /// Extra globs to be ignored while reading files
+    ///
+    ignore: Vec<String>,
+
+    ///
+    /// Language of files matching each glob
+    ///
+    languages: Vec<(GlobMatcher, String)>,
+
+    ///
+    /// Extra [Regex] patterns to be ignored by the tokenizer
+    ///
+    ignore_patterns: Vec<Regex>,
+
+    ///
+    /// Extra [Regex] patterns to be ignored by the tokenizer, matched against
+    /// entire lines
+    ///
+    line_ignore_patterns: Vec<Regex>,
+
+    ///
+    /// Built-in patterns which should not be ignored by the tokenizer
+    ///
+    disabled_builtin_patterns: Vec<BuiltinPattern>,
+
+    ///
+    /// Skip random looking strings like git SHAs, UUIDs or API keys
+    ///
+    ignore_random_strings: bool,
+
+    ///
+    /// Check hyphenated compounds as a whole and rejoin words broken across lines
+    ///
+    hyphenated_compounds: bool,
+
+    ///
+    /// Accept lowercase identifiers made of known words
+    ///
+    compound_words: bool,
+
+    ///
+    /// Minimum length of each word of a compound identifier
+    ///
+    compound_min_part_length: usize,
+
+    ///
+    /// Words shorter than this are not checked
+    ///
+    min_word_length: Option<usize>,
+
+    ///
+    /// How words containing digits are checked
+    ///
+    digits: Digits,
+
+    ///
+    /// How the case of words has to match dictionaries
+    ///
+    case_sensitivity: CaseSensitivity,
+
+    ///
+    /// Format used to print findings
+    ///
+    format: OutputFormat,
+
+    ///
+    /// Severity of findings
+    ///
+    severity: Severity,
+
+    ///
+    /// Files larger than this (in bytes) are skipped
+    ///
+    max_file_size: Option<u64>,
+
+    ///
+    /// Encoding of files matching each glob
+    ///
+    encodings: Vec<(GlobMatcher, Encoding)>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("."),
+            dictionaries: BTreeMap::new(),
+            default_language: DEFAULT_LANGUAGE.to_string(),
+            ignore: Vec::new(),
+            languages: Vec::new(),
+            ignore_patterns: Vec::new(),
+            line_ignore_patterns: Vec::new(),
+            disabled_builtin_patterns: Vec::new(),
+            ignore_random_strings: true,
+            hyphenated_compounds: false,
+            compound_words: false,
+            compound_min_part_length: 3,
+            min_word_length: None,
+            digits: Digits::default(),
+            case_sensitivity: CaseSensitivity::default(),
+            format: OutputFormat::default(),
+            severity: Severity::default(),
+            max_file_size: None,
+            encodings: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    ///
+    /// Find the config for [path] by searching it and all of its parent directories
+    ///
+    /// Returns `None` if no config file is found
+    ///
+    pub fn discover<P: AsRef<Path>>(path: P) -> io::Result<Option<Self>> {
+        let path = path.as_ref().canonicalize()?;
+
+        for dir in path.ancestors().filter(|dir| dir.is_dir()) {
+            let config_path = dir.join(CONFIG_FILE);
+            if config_path.is_file() {
+                return Self::load(config_path).map(Some);
+            }
+
+            let pyproject_path = dir.join(PYPROJECT_FILE);
+            if pyproject_path.is_file() {
+                let content = fs::read_to_string(&pyproject_path)?;
+
+                if let Some(config) = Self::parse_pyproject(&content, dir)? {
+                    return Ok(Some(config));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    ///
+    /// Load the config from the file at [path]
+    ///
+    /// The `[tool.y3]` section is used for `pyproject.toml` files
+    ///
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+
+        let root = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        if path.file_name().is_some_and(|name| name == PYPROJECT_FILE) {
+            return Self::parse_pyproject(&content, root)?.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("No [tool.y3] section in {}", path.display()),
+                )
+            });
+        }
+
+        Self::parse(&content, root)
+    }
+
+    ///
+    /// Parse the config from the content of a `y3.toml` file
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - Content of the config file
+    /// * `root` - Directory containing the config file
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::config::{Config, Severity};
+    ///
+    /// let config = Config::parse("min-word-length = 3\nseverity = \"warning\"", ".").unwrap();
+    ///
+    /// assert_eq!(config.min_word_length(), Some(3));
+    /// assert_eq!(config.severity(), Severity::Warning);
+    /// ```
+    ///
+    pub fn parse<P: AsRef<Path>>(content: &str, root: P) -> io::Result<Self> {
+        let raw: RawConfig = toml::from_str(content).map_err(Self::invalid_config)?;
+
+        Self::from_raw(raw, root.as_ref())
+    }
+
+    ///
+    /// Parse the config from the `[tool.y3]` section of a `pyproject.toml` file
+    ///
+    /// Returns `None` if the section is missing
+    ///
+    fn parse_pyproject(content: &str, root: &Path) -> io::Result<Option<Self>> {
+        let mut table: toml::Table = toml::from_str(content).map_err(Self::invalid_config)?;
+
+        let section = table.remove("tool").and_then(|tool| match tool {
+            toml::Value::Table(mut tool) => tool.remove("y3"),
+            _ => None,
+        });
+
+        let Some(section) = section else {
+            return Ok(None);
+        };
+
+        let raw: RawConfig = section.try_into().map_err(Self::invalid_config)?;
+
+        Self::from_raw(raw, root).map(Some)
+    }
+
+    ///
+    /// Validate the [RawConfig] and resolve its paths against the [root]
+    ///
+    fn from_raw(raw: RawConfig, root: &Path) -> io::Result<Self> {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+
+        let dictionaries = raw
+            .dictionaries
+            .into_iter()
+            .map(|(language, paths)| {
+                let paths = paths.into_iter().map(|path| root.join(path)).collect();
+                (language, paths)
+            })
+            .collect();
+
+        let languages = raw
+            .languages
+            .into_iter()
+            .map(|(glob, language)| Ok((Self::compile_glob(&glob)?, language)))
+            .collect::<io::Result<_>>()?;
+
+        let encodings = raw
+            .encodings
+            .into_iter()
+            .map(|(glob, name)| {
+                let encoding = Encoding::from_name(&name)
+                    .ok_or_else(|| Self::invalid_config(format!("Unknown encoding `{name}`")))?;
+
+                Ok((Self::compile_glob(&glob)?, encoding))
+            })
+            .collect::<io::Result<_>>()?;
+
+        let ignore_patterns = Self::compile_patterns(&raw.ignore_patterns)?;
+        let line_ignore_patterns = Self::compile_patterns(&raw.line_ignore_patterns)?;
+
+        let disabled_builtin_patterns = raw
+            .disable_builtin_patterns
+            .iter()
+            .map(|name| {
+                BuiltinPattern::from_name(name).ok_or_else(|| {
+                    Self::invalid_config(format!("Unknown built-in pattern `{name}`"))
+                })
+            })
+            .collect::<io::Result<_>>()?;
+
+        let digits = match raw.digits {
+            Some(name) => Digits::from_name(&name)
+                .ok_or_else(|| Self::invalid_config(format!("Unknown digits mode `{name}`")))?,
+            None => Digits::default(),
+        };
+
+        let case_sensitivity = match raw.case_sensitivity {
+            Some(name) => CaseSensitivity::from_name(&name).ok_or_else(|| {
+                Self::invalid_config(format!("Unknown case sensitivity `{name}`"))
+            })?,
+            None => CaseSensitivity::default(),
+        };
+
+        Ok(Self {
+            root,
+            dictionaries,
+            default_language: raw.default_language,
+            ignore: raw.ignore,
+            languages,
+            ignore_patterns,
+            line_ignore_patterns,
+            disabled_builtin_patterns,
+            ignore_random_strings: raw.ignore_random_strings,
+            hyphenated_compounds: raw.hyphenated_compounds,
+            compound_words: raw.compound_words,
+            compound_min_part_length: raw.compound_min_part_length,
+            min_word_length: raw.min_word_length,
+            digits,
+            case_sensitivity,
+            format: raw.format,
+            severity: raw.severity,
+            max_file_size: raw.max_file_size,
+            encodings,
+        })
+    }
+
+    ///
+    /// Getter to read the directory containing the config file
+    ///
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    ///
+    /// Getter to read extra word lists for each language
+    ///
+    pub fn dictionaries(&self) -> &BTreeMap<String, Vec<PathBuf>> {
+        &self.dictionaries
+    }
+
+    ///
+    /// Getter to read the language used for files not matched by any glob
+    ///
+    pub fn default_language(&self) -> &str {
+        &self.default_language
+    }
+
+    ///
+    /// Names of all the languages used by the config
+    ///
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.default_language.as_str())
+            .chain(self.languages.iter().map(|(_, language)| language.as_str()))
+            .chain(self.dictionaries.keys().map(String::as_str))
+    }
+
+    ///
+    /// Get the language of the file at [path]
+    ///
+    pub fn language<P: AsRef<Path>>(&self, path: P) -> &str {
+        if self.languages.is_empty() {
+            return &self.default_language;
+        }
+
+        self.relative_path(path)
+            .and_then(|relative| {
+                self.languages
+                    .iter()
+                    .find(|(matcher, _)| matcher.is_match(&relative))
+            })
+            .map_or(&self.default_language, |(_, language)| language)
+    }
+
+    ///
+    /// Get the configured [Encoding] of the file at [path], `None` if it should
+    /// be detected
+    ///
+    pub fn encoding<P: AsRef<Path>>(&self, path: P) -> Option<Encoding> {
+        if self.encodings.is_empty() {
+            return None;
+        }
+
+        let relative = self.relative_path(path)?;
+
+        self.encodings
+            .iter()
+            .find(|(matcher, _)| matcher.is_match(&relative))
+            .map(|(_, encoding)| *encoding)
+    }
+
+    ///
+    /// Getter to read the size limit (in bytes) of checked files
+    ///
+    pub fn max_file_size(&self) -> Option<u64> {
+        self.max_file_size
+    }
+
+    ///
+    /// Getter to read extra globs to be ignored, relative to [Config::root]
+    ///
+    pub fn ignore(&self) -> &[String] {
+        &self.ignore
+    }
+
+    ///
+    /// Getter to read extra [Regex] patterns to be ignored by the tokenizer
+    ///
+    pub fn ignore_patterns(&self) -> &[Regex] {
+        &self.ignore_patterns
+    }
+
+    ///
+    /// Getter to read extra [Regex] patterns to be ignored by the tokenizer,
+    /// matched against entire lines
+    ///
+    pub fn line_ignore_patterns(&self) -> &[Regex] {
+        &self.line_ignore_patterns
+    }
+
+    ///
+    /// Getter to read built-in patterns which should not be ignored by the tokenizer
+    ///
+    pub fn disabled_builtin_patterns(&self) -> &[BuiltinPattern] {
+        &self.disabled_builtin_patterns
+    }
+
+    ///
+    /// Check if random looking strings should be skipped by the tokenizer
+    ///
+    pub fn ignore_random_strings(&self) -> bool {
+        self.ignore_random_strings
+    }
+
+    ///
+    /// Check if hyphenated compounds should be checked as a whole by the tokenizer
+    ///
+    pub fn hyphenated_compounds(&self) -> bool {
+        self.hyphenated_compounds
+    }
+
+    ///
+    /// Minimum length of each word when lowercase identifiers are segmented into
+    /// known words, `None` when compound words are disabled
+    ///
+    pub fn compound_words(&self) -> Option<usize> {
+        self.compound_words.then_some(self.compound_min_part_length)
+    }
+
+    ///
+    /// Getter to read the minimum length of checked words
+    ///
+    pub fn min_word_length(&self) -> Option<usize> {
+        self.min_word_length
+    }
+
+    ///
+    /// Getter to read how words containing digits are checked
+    ///
+    pub fn digits(&self) -> Digits {
+        self.digits
+    }
+
+    ///
+    /// Getter to read how the case of words has to match dictionaries
+    ///
+    pub fn case_sensitivity(&self) -> CaseSensitivity {
+        self.case_sensitivity
+    }
+
+    ///
+    /// Getter to read the [OutputFormat]
+    ///
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    ///
+    /// Getter to read the [Severity] of findings
+    ///
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    ///
+    /// Path of the file at [path] relative to [Config::root], `None` if it's
+    /// outside of it
+    ///
+    /// The file doesn't need to exist, e.g. `--stdin-filename`.
+    ///
+    fn relative_path<P: AsRef<Path>>(&self, path: P) -> Option<PathBuf> {
+        let path = path.as_ref();
+        let path = path
+            .canonicalize()
+            .or_else(|_| std::path::absolute(path))
+            .ok()?;
+
+        path.strip_prefix(&self.root).ok().map(Path::to_path_buf)
+    }
+
+    ///
+    /// Compile the [glob], relative to [Config::root]
+    ///
+    fn compile_glob(glob: &str) -> io::Result<GlobMatcher> {
+        let matcher = GlobBuilder::new(glob)
+            .literal_separator(true)
+            .build()
+            .map_err(|err| Self::invalid_config(format!("Invalid glob pattern: {err}")))?
+            .compile_matcher();
+
+        Ok(matcher)
+    }
+
+    ///
+    /// Compile the regex [patterns]
+    ///
+    fn compile_patterns(patterns: &[String]) -> io::Result<Vec<Regex>> {
+        patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|err| Self::invalid_config(format!("Invalid ignore pattern: {err}")))
+            })
+            .collect()
+    }
+
+    fn invalid_config<E: fmt::Display>(err: E) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, format!("Invalid config: {err}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let content = r#"
+dictionaries = { en_us = ["words.txt"] }
+ignore = ["vendor/**"]
+ignore-patterns = ['^[A-Z]+-\d+$']
+line-ignore-patterns = ['Signed-off-by: .*']
+disable-builtin-patterns = ["number"]
+hyphenated-compounds = true
+compound-words = true
+compound-min-part-length = 4
+digits = "strip"
+case-sensitivity = "insensitive"
+format = "json"
+max-file-size = 1024
+
+[languages]
+"docs/fr/**" = "fr"
+"docs/**" = "de"
+"#;
+
+        let config = Config::parse(content, "project").unwrap();
+
+        assert_eq!(
+            config.dictionaries()["en_us"],
+            vec![PathBuf::from("project/words.txt")]
+        );
+        assert_eq!(config.default_language(), DEFAULT_LANGUAGE);
+        assert_eq!(config.ignore(), ["vendor/**"]);
+        assert!(config.ignore_patterns()[0].is_match("ABC-1234"));
+        assert!(config.line_ignore_patterns()[0].is_match("Signed-off-by: Jhon Doe"));
+        assert_eq!(config.disabled_builtin_patterns(), [BuiltinPattern::Number]);
+        assert!(config.hyphenated_compounds());
+        assert_eq!(config.compound_words(), Some(4));
+        assert_eq!(config.digits(), Digits::Strip);
+        assert_eq!(config.case_sensitivity(), CaseSensitivity::Insensitive);
+        assert_eq!(config.format(), OutputFormat::Json);
+        assert_eq!(config.severity(), Severity::Error);
+        assert_eq!(config.max_file_size(), Some(1024));
+        assert_eq!(
+            config.languages().collect::<Vec<_>>(),
+            vec!["en_us", "fr", "de", "en_us"]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Config::parse("unknown-key = 1", ".").is_err());
+        assert!(Config::parse("ignore-patterns = ['(']", ".").is_err());
+        assert!(Config::parse("format = \"xml\"", ".").is_err());
+        assert!(Config::parse("digits = \"drop\"", ".").is_err());
+        assert!(Config::parse("case-sensitivity = \"upper\"", ".").is_err());
+        assert!(Config::parse("disable-builtin-patterns = [\"uuid\"]", ".").is_err());
+        assert!(Config::parse("[encodings]\n\"*.txt\" = \"ebcdic\"", ".").is_err());
+    }
+
+    #[test]
+    fn test_encoding() {
+        let config = Config::parse("[encodings]\n\"src/**\" = \"latin-1\"", ".").unwrap();
+
+        assert_eq!(config.encoding("src/lib.rs"), Some(Encoding::Latin1));
+        assert_eq!(config.encoding("Cargo.toml"), None);
+    }
+
+    #[test]
+    fn test_parse_pyproject() {
+        let content = r#"
+[project]
+name = "demo"
+
+[tool.y3]
+min-word-length = 4
+"#;
+
+        let config = Config::parse_pyproject(content, Path::new("."))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.min_word_length(), Some(4));
+        assert_eq!(config.compound_words(), None);
+
+        let config = Config::parse_pyproject("[project]\nname = \"demo\"", Path::new("."));
+        assert!(config.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_discover() {
+        let dir = std::env::temp_dir().join(format!("y3_config_{}", std::process::id()));
+        fs::create_dir_all(dir.join("project/src/deep")).unwrap();
+
+        // Discovered roots are canonical, e.g. when the temp dir is a symlink
+        let dir = dir.canonicalize().unwrap();
+        let nested = dir.join("project/src/deep");
+
+        fs::write(dir.join(PYPROJECT_FILE), "[tool.y3]\nmin-word-length = 4\n").unwrap();
+        fs::write(
+            dir.join("project").join(CONFIG_FILE),
+            "min-word-length = 3\n",
+        )
+        .unwrap();
+        // Without a `[tool.y3]` section the search goes on to the parent
+        fs::write(dir.join("project/src").join(PYPROJECT_FILE), "[project]\n").unwrap();
+
+        // The closest config wins, for directories and files alike
+        let config = Config::discover(&nested).unwrap().unwrap();
+        assert_eq!(config.min_word_length(), Some(3));
+        assert_eq!(config.root(), dir.join("project"));
+
+        fs::write(nested.join("file.txt"), "").unwrap();
+        let config = Config::discover(nested.join("file.txt")).unwrap().unwrap();
+        assert_eq!(config.min_word_length(), Some(3));
+
+        fs::remove_file(dir.join("project").join(CONFIG_FILE)).unwrap();
+        let config = Config::discover(&nested).unwrap().unwrap();
+        assert_eq!(config.min_word_length(), Some(4));
+        assert_eq!(config.root(), dir);
+
+        assert!(Config::discover(dir.join("missing")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,626 @@
+//!
+//! # Dictionary
+//!
+//! A set of known words used to decide whether a [Token] is spelled correctly
+//!
+//! ## Format
+//!
+//! Dictionaries are plain text files with one word per line, e.g.
+//! `dictionaries/en_us.txt`. Empty lines and lines starting with `#` are skipped.
+//!
+//! ## Considerations
+//!
+//! - Lowercase words match in lowercase, Title case and ALL-CAPS (e.g. "hello",
+//!   "Hello", "HELLO"), plurals of ALL-CAPS words like "IDs" or "CPUs" count as
+//!   ALL-CAPS
+//! - Words with capitals keep their case, proper nouns like "English" require a
+//!   capital letter (or ALL-CAPS) and mixed case words like "GitHub" or "iPhone" only
+//!   match exactly, see [CaseSensitivity]
+//! - The bundled `en_us` list is all lowercase, so its proper nouns match in any
+//!   case (e.g. "english"). Casing is only enforced for words with capitals from
+//!   other word lists.
+//! - camelCase is split by the tokenizer, the checker looks up "GitHub" as it is
+//!   before falling back to "Git" and "Hub", while "Github" or "github" don't match it
+//! - Words are normalized to NFC, so composed and decomposed accents match
+//! - Curly apostrophes (`’`) match straight ones, and possessives like "user's" or
+//!   "users'" are known when their stem is
+//! - Lookups of lowercase words, or ASCII words up to 64 bytes, don't allocate
+//! - Lowercase identifiers like "filesystem" or "eventloop" can be segmented into
+//!   known words, see [Dictionary::segment]. Typos of known words split into words
+//!   too (e.g. "retrun" into "ret" and "run"), so with `en_us` words a single edit
+//!   away from a known word aren't segmented
+//!
+//! [Token]: crate::tokenizer::Token
+//!
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::Path,
+};
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+///
+/// Default `en_us` word list bundled with the binary
+///
+const EN_US: &str = include_str!("../dictionaries/en_us.txt");
+
+///
+/// Contractions bundled with the `en_us` word list
+///
+const EN_US_CONTRACTIONS: &str = include_str!("../dictionaries/en_us_contractions.txt");
+
+///
+/// Typographic apostrophe, matched as `'`
+///
+pub const CURLY_APOSTROPHE: char = '\u{2019}';
+
+///
+/// Longest ASCII word which is lowercased without allocating in [Dictionary::contains]
+///
+const MAX_STACK_WORD_LENGTH: usize = 64;
+
+///
+/// Maximum number of known words a compound is segmented into
+///
+const MAX_COMPOUND_PARTS: usize = 3;
+
+///
+/// Suffixes which are in the `en_us` word list on their own, but aren't a word of
+/// a compound (e.g. `begin` + `ing` is a typo of `beginning`)
+///
+const EN_US_COMPOUND_SUFFIXES: &[&str] = &[
+    "ance", "ence", "ing", "ings", "ion", "ions", "ism", "ist", "ity", "ment", "ments", "ness",
+    "ous", "ter", "ting", "tion", "tions",
+];
+
+///
+/// How the case of a looked up word has to match the dictionary, see
+/// [Dictionary::set_case_sensitivity]
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CaseSensitivity {
+    ///
+    /// Words with capitals in the dictionary keep their case (e.g. `English`,
+    /// `GitHub`), lowercase ones match in lowercase, Title case and ALL-CAPS
+    ///
+    #[default]
+    Sensitive,
+
+    ///
+    /// Words match in any case
+    ///
+    Insensitive,
+}
+
+impl CaseSensitivity {
+    ///
+    /// List of all the case sensitivities
+    ///
+    pub const ALL: [Self; 2] = [Self::Sensitive, Self::Insensitive];
+
+    ///
+    /// Name of the case sensitivity, as used in config files
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::dictionary::CaseSensitivity;
+    ///
+    /// assert_eq!(CaseSensitivity::Insensitive.name(), "insensitive");
+    /// assert_eq!(CaseSensitivity::from_name("sensitive"), Some(CaseSensitivity::Sensitive));
+    /// ```
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sensitive => "sensitive",
+            Self::Insensitive => "insensitive",
+        }
+    }
+
+    ///
+    /// Find the case sensitivity by its [name]
+    ///
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|case| case.name() == name)
+    }
+}
+
+///
+/// Casing of a word, only letters are considered
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WordCase {
+    ///
+    /// No capitals, e.g. `hello`
+    ///
+    Lower,
+
+    ///
+    /// Only the first letter is a capital, e.g. `Hello`
+    ///
+    Title,
+
+    ///
+    /// Only capitals, e.g. `HELLO`
+    ///
+    Upper,
+
+    ///
+    /// Anything else, e.g. `iPhone` or `GitHub`
+    ///
+    Mixed,
+}
+
+impl WordCase {
+    ///
+    /// Find the casing of the [word]
+    ///
+    fn of(word: &str) -> Self {
+        let mut letters = word.chars().filter(|c| c.is_alphabetic());
+        let first_upper = letters.next().is_some_and(char::is_uppercase);
+
+        let (mut upper, mut lower) = (false, false);
+        for c in letters {
+            upper |= c.is_uppercase();
+            lower |= c.is_lowercase();
+        }
+
+        match (first_upper, upper, lower) {
+            (false, false, _) => Self::Lower,
+            (true, false, _) => Self::Title,
+            (true, true, false) => Self::Upper,
+            _ if Self::is_upper_plural(word) => Self::Upper,
+            _ => Self::Mixed,
+        }
+    }
+
+    ///
+    /// Check if the [word] is the plural of an ALL-CAPS word, e.g. `IDs` or `CPUs`
+    ///
+    fn is_upper_plural(word: &str) -> bool {
+        word.strip_suffix('s').is_some_and(|stem| {
+            stem.chars().filter(|c| c.is_alphabetic()).count() >= 2 && Self::of(stem) == Self::Upper
+        })
+    }
+}
+
+///
+/// A set of known words to spell check [Token]'s against
+///
+/// [Token]: crate::tokenizer::Token
+///
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    ///
+    /// Lowercase words known to the dictionary
+    ///
+    words: HashSet<String>,
+
+    ///
+    /// Words with capitals (e.g. `English`, `GitHub`), by their lowercase form
+    ///
+    cased_words: HashMap<String, Vec<String>>,
+
+    ///
+    /// How the case of looked up words has to match
+    ///
+    case_sensitivity: CaseSensitivity,
+
+    ///
+    /// Reject English suffixes and typos of known words as compounds, only set
+    /// for [Dictionary::en_us], see [Dictionary::segment]
+    ///
+    en_us_compounds: bool,
+}
+
+impl Dictionary {
+    ///
+    /// Create an empty instance of [Dictionary]
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::dictionary::Dictionary;
+    ///
+    /// let dictionary = Dictionary::new();
+    /// assert!(dictionary.is_empty());
+    /// ```
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Create an instance of [Dictionary] from the bundled `en_us` word list
+    ///
+    pub fn en_us() -> Self {
+        let mut dictionary = Self::new();
+        dictionary.extend_from_str(EN_US);
+        dictionary.extend_from_str(EN_US_CONTRACTIONS);
+        dictionary.en_us_compounds = true;
+        dictionary
+    }
+
+    ///
+    /// Load words from the word list at [path] into the dictionary
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to a word list with one word per line
+    ///
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let content = fs::read_to_string(path)?;
+        self.extend_from_str(&content);
+
+        Ok(())
+    }
+
+    ///
+    /// Add words from a word list with one word per line
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::dictionary::Dictionary;
+    ///
+    /// let mut dictionary = Dictionary::new();
+    /// dictionary.extend_from_str("hello\n# comment\nworld\n");
+    ///
+    /// assert_eq!(dictionary.len(), 2);
+    /// ```
+    ///
+    pub fn extend_from_str(&mut self, content: &str) {
+        for line in content.lines() {
+            let word = line.trim();
+
+            if word.is_empty() || word.starts_with('#') {
+                continue;
+            }
+
+            self.insert(word);
+        }
+    }
+
+    ///
+    /// Add a single [word] to the dictionary
+    ///
+    pub fn insert(&mut self, word: &str) {
+        let word = normalize_word(word);
+
+        if !word.chars().any(char::is_uppercase) {
+            self.words.insert(word);
+            return;
+        }
+
+        let forms = self.cased_words.entry(word.to_lowercase()).or_default();
+        if !forms.contains(&word) {
+            forms.push(word);
+        }
+    }
+
+    ///
+    /// Match the case of looked up words with [case_sensitivity], defaults to
+    /// [CaseSensitivity::Sensitive]
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::dictionary::{CaseSensitivity, Dictionary};
+    ///
+    /// let mut dictionary = Dictionary::new();
+    /// dictionary.extend_from_str("hello\nEnglish\nGitHub\niPhone\n");
+    ///
+    /// assert!(dictionary.contains("HELLO"));
+    /// assert!(!dictionary.contains("hELLO"));
+    ///
+    /// // Proper nouns require a capital letter
+    /// assert!(dictionary.contains("English"));
+    /// assert!(dictionary.contains("ENGLISH"));
+    /// assert!(!dictionary.contains("english"));
+    ///
+    /// // Mixed case words match exactly
+    /// assert!(dictionary.contains("GitHub"));
+    /// assert!(!dictionary.contains("Github"));
+    /// assert!(!dictionary.contains("IPHONE"));
+    ///
+    /// dictionary.set_case_sensitivity(CaseSensitivity::Insensitive);
+    ///
+    /// assert!(dictionary.contains("english"));
+    /// assert!(dictionary.contains("Github"));
+    /// ```
+    ///
+    pub fn set_case_sensitivity(&mut self, case_sensitivity: CaseSensitivity) {
+        self.case_sensitivity = case_sensitivity;
+    }
+
+    ///
+    /// Check if the [word] is known to the dictionary, ignoring Unicode composition
+    /// and the style of apostrophes
+    ///
+    /// The case has to match as configured, see [Dictionary::set_case_sensitivity].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::dictionary::Dictionary;
+    ///
+    /// let mut dictionary = Dictionary::new();
+    /// dictionary.insert("hello");
+    /// dictionary.insert("Zürich");
+    ///
+    /// assert!(dictionary.contains("Hello"));
+    /// assert!(!dictionary.contains("helo"));
+    ///
+    /// // `u` followed by a combining diaeresis
+    /// assert!(dictionary.contains("ZU\u{308}RICH"));
+    ///
+    /// let dictionary = Dictionary::en_us();
+    ///
+    /// assert!(dictionary.contains("don't"));
+    /// assert!(dictionary.contains("It’s"));
+    /// assert!(dictionary.contains("user's"));
+    /// assert!(dictionary.contains("users'"));
+    /// assert!(!dictionary.contains("usre's"));
+    ///
+    /// // Plurals of acronyms are ALL-CAPS rather than mixed case
+    /// assert!(dictionary.contains("IDs"));
+    /// assert!(dictionary.contains("CPUs"));
+    /// ```
+    ///
+    pub fn contains(&self, word: &str) -> bool {
+        // Words are stored in NFC, e.g. `e` followed by a combining accent is `é`
+        if !word.is_ascii()
+            && (word.contains(CURLY_APOSTROPHE) || is_nfc_quick(word.chars()) != IsNormalized::Yes)
+        {
+            return self.contains_normalized(&normalize_word(word));
+        }
+
+        self.contains_normalized(word)
+    }
+
+    ///
+    /// Same as [Dictionary::contains], for a [word] in NFC with straight apostrophes
+    ///
+    fn contains_normalized(&self, word: &str) -> bool {
+        if self.contains_cased(word) {
+            return true;
+        }
+
+        // Possessives are known when their stem is, e.g. `user's` or `users'`
+        let stem = word
+            .strip_suffix("'s")
+            .or_else(|| word.strip_suffix("'S"))
+            .or_else(|| word.strip_suffix('\''));
+
+        stem.is_some_and(|stem| !stem.is_empty() && self.contains_cased(stem))
+    }
+
+    ///
+    /// Check if the [word] is known with the configured [CaseSensitivity], without
+    /// further normalization
+    ///
+    fn contains_cased(&self, word: &str) -> bool {
+        if !word.chars().any(char::is_uppercase) {
+            return self.words.contains(word)
+                || (self.case_sensitivity == CaseSensitivity::Insensitive
+                    && self.cased_words.contains_key(word));
+        }
+
+        with_lowercase(word, |lowercase| self.contains_lowercase(word, lowercase))
+    }
+
+    ///
+    /// Check if the [word] with capitals is known by its [lowercase] form
+    ///
+    fn contains_lowercase(&self, word: &str, lowercase: &str) -> bool {
+        let insensitive = self.case_sensitivity == CaseSensitivity::Insensitive;
+        let case = WordCase::of(word);
+
+        // Lowercase words also match in Title case and ALL-CAPS
+        if self.words.contains(lowercase) && (insensitive || case != WordCase::Mixed) {
+            return true;
+        }
+
+        let Some(forms) = self.cased_words.get(lowercase) else {
+            return false;
+        };
+
+        // Proper nouns also match in ALL-CAPS, e.g. `ENGLISH` in a heading
+        insensitive
+            || forms.iter().any(|form| {
+                form == word || (case == WordCase::Upper && WordCase::of(form) == WordCase::Title)
+            })
+    }
+
+    ///
+    /// Segment a lowercase compound [word] into 2 or 3 known words, each at least
+    /// [min_part_length] characters long
+    ///
+    /// Fewer parts are preferred, then longer leading parts. Words with uppercase
+    /// letters or anything but letters (e.g. `camelCase`, `don't`) aren't segmented.
+    ///
+    /// For [Dictionary::en_us], suffixes like `ing` or `ment` are never a part, and
+    /// words a single edit away from a known word (e.g. `begining`) are typos
+    /// rather than compounds. Other languages have no such rules, their words are
+    /// segmented as long as the parts are known.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::dictionary::Dictionary;
+    ///
+    /// let dictionary = Dictionary::en_us();
+    ///
+    /// assert_eq!(dictionary.segment("eventloop", 3), Some(vec!["event", "loop"]));
+    /// assert_eq!(dictionary.segment("filesystem", 3), Some(vec!["file", "system"]));
+    /// assert_eq!(dictionary.segment("readfilesystem", 4), Some(vec!["read", "file", "system"]));
+    ///
+    /// assert_eq!(dictionary.segment("fileio", 3), None);
+    /// assert_eq!(dictionary.segment("eventLoop", 3), None);
+    /// assert_eq!(dictionary.segment("arguement", 3), None);
+    /// assert_eq!(dictionary.segment("retrun", 3), None);
+    ///
+    /// let mut dictionary = Dictionary::new();
+    /// dictionary.extend_from_str("begin\ning\n");
+    ///
+    /// assert_eq!(dictionary.segment("begining", 3), Some(vec!["begin", "ing"]));
+    /// ```
+    ///
+    pub fn segment<'w>(&self, word: &'w str, min_part_length: usize) -> Option<Vec<&'w str>> {
+        if !word.chars().all(char::is_lowercase) {
+            return None;
+        }
+
+        let segments = (2..=MAX_COMPOUND_PARTS)
+            .find_map(|parts| self.segment_into(word, parts, min_part_length.max(1)))?;
+
+        // Only words which can be segmented pay for looking up their typos
+        (!self.en_us_compounds || !self.is_near_miss(word)).then_some(segments)
+    }
+
+    ///
+    /// Segment the [word] into exactly [parts] known words, see [Dictionary::segment]
+    ///
+    fn segment_into<'w>(
+        &self,
+        word: &'w str,
+        parts: usize,
+        min_part_length: usize,
+    ) -> Option<Vec<&'w str>> {
+        if parts == 1 {
+            return (self.is_compound_part(word, min_part_length) && self.contains(word))
+                .then(|| vec![word]);
+        }
+
+        // Byte offsets where the leading part is long enough, longest first
+        let splits: Vec<usize> = word
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .skip(min_part_length)
+            .collect();
+
+        splits.into_iter().rev().find_map(|idx| {
+            let (head, tail) = word.split_at(idx);
+
+            if !self.is_compound_part(head, min_part_length) || !self.contains(head) {
+                return None;
+            }
+
+            let mut segments = self.segment_into(tail, parts - 1, min_part_length)?;
+            segments.insert(0, head);
+
+            Some(segments)
+        })
+    }
+
+    ///
+    /// Check if the [part] may be a word of a compound, regardless of whether it's
+    /// known
+    ///
+    fn is_compound_part(&self, part: &str, min_part_length: usize) -> bool {
+        part.chars().count() >= min_part_length
+            && !(self.en_us_compounds && EN_US_COMPOUND_SUFFIXES.contains(&part))
+    }
+
+    ///
+    /// Check if a known lowercase word is a single edit away from the lowercase
+    /// [word], i.e. with a letter inserted, deleted or replaced, or two adjacent
+    /// letters swapped
+    ///
+    /// Only ASCII letters are inserted or used as replacements.
+    ///
+    fn is_near_miss(&self, word: &str) -> bool {
+        let chars: Vec<char> = word.chars().collect();
+        let mut candidate = String::with_capacity(word.len() + 4);
+
+        let mut is_known = |pieces: [&[char]; 3]| {
+            candidate.clear();
+            candidate.extend(pieces.into_iter().flatten());
+
+            self.words.contains(candidate.as_str())
+        };
+
+        for idx in 0..=chars.len() {
+            let (head, tail) = chars.split_at(idx);
+
+            if let [first, rest @ ..] = tail {
+                if is_known([head, &[], rest]) {
+                    return true;
+                }
+
+                if let [second, rest @ ..] = rest {
+                    if is_known([head, &[*second, *first], rest]) {
+                        return true;
+                    }
+                }
+            }
+
+            for letter in 'a'..='z' {
+                if is_known([head, &[letter], tail]) {
+                    return true;
+                }
+
+                if let [first, rest @ ..] = tail {
+                    if *first != letter && is_known([head, &[letter], rest]) {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    ///
+    /// Number of words in the dictionary
+    ///
+    pub fn len(&self) -> usize {
+        self.words.len() + self.cased_words.values().map(Vec::len).sum::<usize>()
+    }
+
+    ///
+    /// Check if the dictionary has no words
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.cased_words.is_empty()
+    }
+}
+
+///
+/// Normalize the [word] as stored in a [Dictionary], in NFC with straight
+/// apostrophes
+///
+fn normalize_word(word: &str) -> String {
+    word.nfc()
+        .map(|c| if c == CURLY_APOSTROPHE { '\'' } else { c })
+        .collect()
+}
+
+///
+/// Normalize the [word] with [normalize_word] and lowercase it, to compare words
+/// in any case
+///
+pub(crate) fn fold_word(word: &str) -> String {
+    normalize_word(word).to_lowercase()
+}
+
+///
+/// Call [f] with the lowercase form of the [word]
+///
+fn with_lowercase<T>(word: &str, f: impl FnOnce(&str) -> T) -> T {
+    // Short ASCII words are lowercased on the stack instead of allocating
+    if word.is_ascii() && word.len() <= MAX_STACK_WORD_LENGTH {
+        let mut buf = [0; MAX_STACK_WORD_LENGTH];
+        let buf = &mut buf[..word.len()];
+
+        buf.copy_from_slice(word.as_bytes());
+        buf.make_ascii_lowercase();
+
+        if let Ok(lowercase) = std::str::from_utf8(buf) {
+            return f(lowercase);
+        }
+    }
+
+    f(&word.to_lowercase())
+}
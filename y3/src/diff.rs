@@ -0,0 +1,383 @@
+//!
+//! # Diff
+//!
+//! Parses unified diffs (e.g. output of `git diff`) to find lines which were added
+//! or modified, so only those are spell checked.
+//!
+//! ## Working
+//!
+//! - Track the target file of each file section using `+++ <path>` headers
+//! - Read the new file line range of each hunk from `@@ -a,b +c,d @@` headers
+//! - Record line numbers of every `+` line in the hunk
+//!
+//! ## Considerations
+//!
+//! - Deleted files (`+++ /dev/null`) have no changed lines
+//! - Default git prefix (`b/`) is stripped from target paths, [ChangedLines::from_git]
+//!   passes it explicitly so `diff.noprefix` or `diff.mnemonicPrefix` in the git
+//!   config don't change it
+//! - Untracked files aren't part of `git diff`, so they aren't checked until they
+//!   are added (e.g. `git add -N`)
+//! - Line numbers are 1-based, same as [Position::line_no]
+//!
+//! [Position::line_no]: crate::tokenizer::Position::line_no
+//!
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+///
+/// Lines added or modified by a diff, grouped by file path
+///
+#[derive(Debug, Default)]
+pub struct ChangedLines {
+    ///
+    /// 1-based line numbers of changed lines for each target file path
+    ///
+    files: HashMap<PathBuf, BTreeSet<usize>>,
+}
+
+impl ChangedLines {
+    ///
+    /// Parse a unified diff and collect added or modified lines
+    ///
+    /// # Arguments
+    ///
+    /// * `diff` - Content of the unified diff
+    /// * `root` - Directory the paths in the diff are relative to
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::diff::ChangedLines;
+    ///
+    /// let diff = "\
+    /// --- a/notes.txt
+    /// +++ b/notes.txt
+    /// @@ -1,2 +1,2 @@
+    ///  first line
+    /// -secnd line
+    /// +second line
+    /// ";
+    ///
+    /// let changed = ChangedLines::parse(diff, ".");
+    ///
+    /// assert!(changed.contains("./notes.txt", 2));
+    /// assert!(!changed.contains("./notes.txt", 1));
+    /// ```
+    ///
+    pub fn parse<P: AsRef<Path>>(diff: &str, root: P) -> Self {
+        let root = root.as_ref();
+        let mut files: HashMap<PathBuf, BTreeSet<usize>> = HashMap::new();
+
+        let mut current: Option<PathBuf> = None;
+        let mut line_no = 0;
+        let mut old_remaining: usize = 0;
+        let mut new_remaining: usize = 0;
+
+        for line in diff.lines() {
+            // Inside of a hunk, lines are interpreted by their first character
+            if old_remaining > 0 || new_remaining > 0 {
+                match line.chars().next() {
+                    Some('+') => {
+                        if let Some(ref path) = current {
+                            files.entry(path.clone()).or_default().insert(line_no);
+                        }
+                        line_no += 1;
+                        new_remaining = new_remaining.saturating_sub(1);
+                    }
+                    Some('-') => old_remaining = old_remaining.saturating_sub(1),
+                    Some('\\') => {} // "\ No newline at end of file"
+                    _ => {
+                        line_no += 1;
+                        old_remaining = old_remaining.saturating_sub(1);
+                        new_remaining = new_remaining.saturating_sub(1);
+                    }
+                }
+                continue;
+            }
+
+            if let Some(target) = line.strip_prefix("+++ ") {
+                current = Self::parse_target(target).map(|path| {
+                    let path = root.join(path);
+                    // Walked paths may be spelled differently, e.g. `./src/lib.rs`
+                    // and `/repo/src/lib.rs`, so prefer canonical paths
+                    path.canonicalize().unwrap_or(path)
+                });
+            } else if let Some(header) = line.strip_prefix("@@ ") {
+                if let Some((old, new)) = Self::parse_hunk_header(header) {
+                    line_no = new.0;
+                    old_remaining = old.1;
+                    new_remaining = new.1;
+                }
+            }
+        }
+
+        Self { files }
+    }
+
+    ///
+    /// Run `git diff` and collect added or modified lines
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory inside of the git repository
+    /// * `rev` - Revision to compare the working tree against, e.g. `main`
+    /// * `staged` - Compare staged changes instead of the working tree
+    ///
+    pub fn from_git<P: AsRef<Path>>(dir: P, rev: Option<&str>, staged: bool) -> io::Result<Self> {
+        let dir = dir.as_ref();
+
+        let root = Self::git(dir, &["rev-parse", "--show-toplevel"])?;
+
+        // Prefixes are set explicitly, `parse_target` only strips `b/`
+        let mut args = vec![
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            "-U0",
+        ];
+        if staged {
+            args.push("--staged");
+        }
+        if let Some(rev) = rev {
+            args.push(rev);
+        }
+
+        let diff = Self::git(dir, &args)?;
+
+        Ok(Self::parse(&diff, root.trim_end()))
+    }
+
+    ///
+    /// Check if the [line_no] of the file at [path] was changed
+    ///
+    pub fn contains<P: AsRef<Path>>(&self, path: P, line_no: usize) -> bool {
+        self.lines(path)
+            .is_some_and(|lines| lines.contains(&line_no))
+    }
+
+    ///
+    /// Get changed line numbers of the file at [path]
+    ///
+    pub fn lines<P: AsRef<Path>>(&self, path: P) -> Option<&BTreeSet<usize>> {
+        let path = path.as_ref();
+
+        self.files
+            .get(path)
+            .or_else(|| self.files.get(&path.canonicalize().ok()?))
+    }
+
+    ///
+    /// Number of files with changed lines
+    ///
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    ///
+    /// Check if the diff has no changed lines
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    ///
+    /// Add the changed lines of [other], e.g. the diff of another repository
+    ///
+    pub fn merge(&mut self, other: Self) {
+        for (path, lines) in other.files {
+            self.files.entry(path).or_default().extend(lines);
+        }
+    }
+
+    ///
+    /// Extract the target path from the `+++ <path>` header
+    ///
+    /// Returns `None` for deleted files (`/dev/null`)
+    ///
+    fn parse_target(target: &str) -> Option<PathBuf> {
+        // Non git diffs may append a timestamp after a tab
+        let target = target.split('\t').next().unwrap_or_default().trim_end();
+        let target = target
+            .strip_prefix('"')
+            .and_then(|t| t.strip_suffix('"'))
+            .unwrap_or(target);
+
+        if target == "/dev/null" {
+            return None;
+        }
+
+        Some(PathBuf::from(target.strip_prefix("b/").unwrap_or(target)))
+    }
+
+    ///
+    /// Parse `-a,b +c,d @@` into `((a, b), (c, d))`
+    ///
+    /// Omitted counts default to `1`, as per the unified diff format
+    ///
+    fn parse_hunk_header(header: &str) -> Option<((usize, usize), (usize, usize))> {
+        let mut parts = header.split_whitespace();
+
+        let old = Self::parse_range(parts.next()?.strip_prefix('-')?)?;
+        let new = Self::parse_range(parts.next()?.strip_prefix('+')?)?;
+
+        Some((old, new))
+    }
+
+    ///
+    /// Parse `start,count` range of a hunk header
+    ///
+    fn parse_range(range: &str) -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    }
+
+    ///
+    /// Run a git command in [dir] and return its stdout
+    ///
+    fn git(dir: &Path, args: &[&str]) -> io::Result<String> {
+        let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        String::from_utf8(output.stdout)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    #[test]
+    fn test_parse_added_and_modified_lines() {
+        let diff = r#"diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ pub mod tokenizer;
+-pub mod reder;
++pub mod reader;
++pub mod dictionary;
+ pub mod diff;
+@@ -10,0 +12,1 @@ fn main() {
++// tpyo
+"#;
+
+        let changed = ChangedLines::parse(diff, "repo");
+        let lines = changed.lines("repo/src/lib.rs").unwrap();
+
+        assert_eq!(lines.iter().copied().collect::<Vec<_>>(), vec![2, 3, 12]);
+    }
+
+    #[test]
+    fn test_parse_multiple_files() {
+        let diff = r#"--- a/one.txt
++++ b/one.txt
+@@ -1 +1 @@
+-old
++new
+--- a/two.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+--- /dev/null
++++ b/three.txt
+@@ -0,0 +1,2 @@
++-- starts with dashes
+++++ starts with pluses
+"#;
+
+        let changed = ChangedLines::parse(diff, "");
+
+        assert_eq!(changed.len(), 2);
+        assert!(changed.contains("one.txt", 1));
+        assert!(changed.lines("two.txt").is_none());
+        assert!(changed.contains("three.txt", 1));
+        assert!(changed.contains("three.txt", 2));
+    }
+
+    #[test]
+    fn test_from_git_ignores_prefix_config() {
+        let dir = env::temp_dir().join(format!("y3_diff_git_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let git = |args: &[&str]| ChangedLines::git(&dir, args).unwrap();
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "y3@example.com"]);
+        git(&["config", "user.name", "y3"]);
+        git(&["config", "diff.noprefix", "true"]);
+
+        fs::write(dir.join("notes.txt"), "first\nsecond\n").unwrap();
+        git(&["add", "notes.txt"]);
+        git(&["-c", "commit.gpgsign=false", "commit", "-q", "-m", "init"]);
+        fs::write(dir.join("notes.txt"), "first\nsecnd\n").unwrap();
+
+        let changed = ChangedLines::from_git(&dir, None, false).unwrap();
+
+        git(&["config", "--unset", "diff.noprefix"]);
+        git(&["config", "diff.mnemonicPrefix", "true"]);
+        let mnemonic = ChangedLines::from_git(&dir, None, false).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(changed.contains(dir.join("notes.txt"), 2));
+        assert!(mnemonic.contains(dir.join("notes.txt"), 2));
+        assert_eq!(changed.len(), 1);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut changed = ChangedLines::parse("+++ b/one.txt\n@@ -1 +1 @@\n-old\n+new\n", "a");
+        changed.merge(ChangedLines::parse(
+            "+++ b/one.txt\n@@ -3 +3 @@\n-old\n+new\n+++ b/two.txt\n@@ -1 +1 @@\n-old\n+new\n",
+            "b",
+        ));
+        changed.merge(ChangedLines::parse(
+            "+++ b/one.txt\n@@ -5 +5 @@\n-old\n+new\n",
+            "a",
+        ));
+
+        assert_eq!(changed.len(), 3);
+        assert_eq!(
+            changed
+                .lines("a/one.txt")
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>(),
+            [&1, &5]
+        );
+        assert!(changed.contains("b/one.txt", 3));
+        assert!(changed.contains("b/two.txt", 1));
+    }
+
+    #[test]
+    fn test_parse_hunk_header() {
+        assert_eq!(
+            ChangedLines::parse_hunk_header("-1,3 +1,4 @@"),
+            Some(((1, 3), (1, 4)))
+        );
+        assert_eq!(
+            ChangedLines::parse_hunk_header("-5 +7 @@ fn main()"),
+            Some(((5, 1), (7, 1)))
+        );
+        assert_eq!(ChangedLines::parse_hunk_header("garbage"), None);
+    }
+}
@@ -0,0 +1,352 @@
+//!
+//! # Encoding
+//!
+//! Detection of binary files and decoding of text files before they are tokenized
+//!
+//! ## Working
+//!
+//! - A byte order mark selects UTF-8, UTF-16LE or UTF-16BE
+//! - Only the first [SNIFF_LENGTH] bytes are looked at to detect the encoding
+//! - Files with NUL bytes are binary
+//! - Valid UTF-8 is used as is
+//! - UTF-8 with some invalid bytes is still UTF-8 if it has valid multi-byte
+//!   characters too, e.g. a single corrupted byte in an otherwise UTF-8 file
+//! - Invalid UTF-8 without any multi-byte characters, made of printable Latin-1
+//!   characters only, is decoded as Latin-1, anything else is binary
+//! - Latin-1 is decoded as Windows-1252, so bytes `0x80` to `0x9F` are smart
+//!   quotes, dashes and the like instead of control characters
+//!
+//! ## Considerations
+//!
+//! - UTF-16 without a byte order mark is full of NUL bytes and detected as binary,
+//!   the [Encoding] of such files has to be configured
+//! - Configured encodings decode lossily, invalid sequences become `U+FFFD`
+//! - Latin-1 files which are plain ASCII for the first [SNIFF_LENGTH] bytes are
+//!   detected as UTF-8, their later Latin-1 characters become `U+FFFD`
+//!
+
+///
+/// Number of bytes looked at to detect the encoding and binary files
+///
+pub const SNIFF_LENGTH: usize = 8 * 1024;
+
+///
+/// Byte order mark of UTF-8
+///
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+///
+/// Byte order mark of UTF-16LE
+///
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+
+///
+/// Byte order mark of UTF-16BE
+///
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+///
+/// Text encodings supported by the tokenizer
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+impl Encoding {
+    ///
+    /// Name of the encoding as written in the config file
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf-8",
+            Self::Utf16Le => "utf-16le",
+            Self::Utf16Be => "utf-16be",
+            Self::Latin1 => "latin-1",
+        }
+    }
+
+    ///
+    /// Find the encoding by its name, case insensitive
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::encoding::Encoding;
+    ///
+    /// assert_eq!(Encoding::from_name("UTF-16LE"), Some(Encoding::Utf16Le));
+    /// assert_eq!(Encoding::from_name("iso-8859-1"), Some(Encoding::Latin1));
+    /// assert_eq!(Encoding::from_name("cp1252"), Some(Encoding::Latin1));
+    /// assert_eq!(Encoding::from_name("ebcdic"), None);
+    /// ```
+    ///
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(Self::Utf8),
+            "utf-16le" | "utf16le" => Some(Self::Utf16Le),
+            "utf-16be" | "utf16be" => Some(Self::Utf16Be),
+            "latin-1" | "latin1" | "iso-8859-1" | "windows-1252" | "cp1252" => Some(Self::Latin1),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Detect the encoding of [bytes], returns `None` for binary content
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::encoding::{detect, Encoding};
+    ///
+    /// assert_eq!(detect("naïve".as_bytes()), Some(Encoding::Utf8));
+    /// assert_eq!(detect(b"na\xefve"), Some(Encoding::Latin1));
+    /// assert_eq!(detect(b"na\xc3\xafve caf\xe9"), Some(Encoding::Utf8));
+    /// assert_eq!(detect(b"\x93quoted\x94"), Some(Encoding::Latin1));
+    /// assert_eq!(detect(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), None);
+    /// ```
+    ///
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(UTF8_BOM) {
+            return Some(Self::Utf8);
+        }
+        if bytes.starts_with(UTF16LE_BOM) {
+            return Some(Self::Utf16Le);
+        }
+        if bytes.starts_with(UTF16BE_BOM) {
+            return Some(Self::Utf16Be);
+        }
+
+        let sniffed = &bytes[..bytes.len().min(SNIFF_LENGTH)];
+        if sniffed.contains(&0) {
+            return None;
+        }
+
+        let (is_valid, has_multi_byte) = inspect_utf8(sniffed, sniffed.len() < bytes.len());
+        if is_valid || has_multi_byte {
+            return Some(Self::Utf8);
+        }
+
+        // Control characters don't show up in Latin-1 text
+        match sniffed.iter().all(|b| is_printable_latin1(*b)) {
+            true => Some(Self::Latin1),
+            false => None,
+        }
+    }
+
+    ///
+    /// Decode [bytes] into a [String], skipping the byte order mark of the encoding
+    ///
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::Utf8 => {
+                let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+
+                String::from_utf8_lossy(bytes).into_owned()
+            }
+            Self::Utf16Le => {
+                let bytes = bytes.strip_prefix(UTF16LE_BOM).unwrap_or(bytes);
+
+                decode_utf16(bytes, u16::from_le_bytes)
+            }
+            Self::Utf16Be => {
+                let bytes = bytes.strip_prefix(UTF16BE_BOM).unwrap_or(bytes);
+
+                decode_utf16(bytes, u16::from_be_bytes)
+            }
+            Self::Latin1 => bytes.iter().map(|b| decode_windows1252(*b)).collect(),
+        }
+    }
+}
+
+///
+/// Detect the encoding of [bytes], same as [Encoding::detect]
+///
+pub fn detect(bytes: &[u8]) -> Option<Encoding> {
+    Encoding::detect(bytes)
+}
+
+///
+/// Decode [bytes] as text, returns `None` for binary content
+///
+/// Valid UTF-8 without a byte order mark is used as is, without copying it.
+///
+/// # Example
+///
+/// ```rust
+/// use y3::encoding::decode_owned;
+///
+/// assert_eq!(decode_owned(b"hello".to_vec(), None).as_deref(), Some("hello"));
+/// assert_eq!(decode_owned(b"caf\xe9".to_vec(), None).as_deref(), Some("café"));
+/// assert_eq!(decode_owned(b"\0\0".to_vec(), None), None);
+/// ```
+///
+/// # Arguments
+///
+/// * `bytes` - Content of a file
+/// * `encoding` - Encoding of the content, detected when `None`
+///
+pub fn decode_owned(bytes: Vec<u8>, encoding: Option<Encoding>) -> Option<String> {
+    let encoding = encoding.or_else(|| Encoding::detect(&bytes))?;
+
+    if encoding == Encoding::Utf8 && !bytes.starts_with(UTF8_BOM) {
+        return Some(
+            String::from_utf8(bytes)
+                .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned()),
+        );
+    }
+
+    Some(encoding.decode(&bytes))
+}
+
+///
+/// Check if [bytes] are valid UTF-8, and if they contain any valid multi-byte
+/// characters
+///
+/// A character cut off at the end of [bytes] is not an error when [is_cut_off],
+/// i.e. [bytes] are only the start of a longer file.
+///
+fn inspect_utf8(mut bytes: &[u8], is_cut_off: bool) -> (bool, bool) {
+    let mut is_valid = true;
+    let mut has_multi_byte = false;
+
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => return (is_valid, has_multi_byte || !text.is_ascii()),
+            Err(err) => {
+                let (valid, rest) = bytes.split_at(err.valid_up_to());
+                has_multi_byte |= !valid.is_ascii();
+
+                match err.error_len() {
+                    Some(len) => {
+                        is_valid = false;
+                        bytes = &rest[len..];
+                    }
+                    None => return (is_valid && is_cut_off, has_multi_byte),
+                }
+            }
+        }
+    }
+}
+
+///
+/// Check if the [byte] is a printable Latin-1 character or common whitespace
+///
+/// Bytes `0x80` to `0x9F` are printable Windows-1252 characters, see
+/// [decode_windows1252].
+///
+fn is_printable_latin1(byte: u8) -> bool {
+    match byte {
+        b'\t' | b'\n' | b'\r' | 0x0C => true,
+        0x00..=0x1F | 0x7F => false,
+        _ => true,
+    }
+}
+
+///
+/// Decode a Latin-1 [byte], with `0x80` to `0x9F` read as Windows-1252
+///
+/// Those bytes are control characters in Latin-1, which don't show up in text,
+/// while Windows-1252 uses them for punctuation like `“` or `–`. The few bytes
+/// undefined in Windows-1252 are kept as Latin-1.
+///
+fn decode_windows1252(byte: u8) -> char {
+    match byte {
+        0x80 => '€',
+        0x82 => '‚',
+        0x83 => 'ƒ',
+        0x84 => '„',
+        0x85 => '…',
+        0x86 => '†',
+        0x87 => '‡',
+        0x88 => 'ˆ',
+        0x89 => '‰',
+        0x8A => 'Š',
+        0x8B => '‹',
+        0x8C => 'Œ',
+        0x8E => 'Ž',
+        0x91 => '‘',
+        0x92 => '’',
+        0x93 => '“',
+        0x94 => '”',
+        0x95 => '•',
+        0x96 => '–',
+        0x97 => '—',
+        0x98 => '˜',
+        0x99 => '™',
+        0x9A => 'š',
+        0x9B => '›',
+        0x9C => 'œ',
+        0x9E => 'ž',
+        0x9F => 'Ÿ',
+        _ => byte as char,
+    }
+}
+
+///
+/// Decode UTF-16 code units read from pairs of [bytes] with [from_bytes]
+///
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]));
+
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect(b"plain text\n"), Some(Encoding::Utf8));
+        assert_eq!(detect(b"\xEF\xBB\xBFwith bom"), Some(Encoding::Utf8));
+        assert_eq!(detect(b"\xFF\xFEh\0i\0"), Some(Encoding::Utf16Le));
+        assert_eq!(detect(b"\xFE\xFF\0h\0i"), Some(Encoding::Utf16Be));
+        assert_eq!(detect(b"caf\xE9 cr\xE8me"), Some(Encoding::Latin1));
+        assert_eq!(detect(b"text\0with nul"), None);
+        assert_eq!(detect(b"\x7FELF\x02\x01\x01\0\0"), None);
+    }
+
+    #[test]
+    fn test_detect_invalid_utf8() {
+        // A stray byte doesn't turn UTF-8 into Latin-1
+        assert_eq!(detect(b"cr\xC3\xA8me \xE9"), Some(Encoding::Utf8));
+        assert_eq!(detect(b"caf\xE9"), Some(Encoding::Latin1));
+        assert_eq!(detect(b"\x1B[31mred\xE9"), None);
+        assert_eq!(
+            detect(b"it\x92s \x93quoted\x94 \x96 caf\xE9"),
+            Some(Encoding::Latin1)
+        );
+
+        // Only the start of the content is looked at
+        let mut bytes = "é".repeat(SNIFF_LENGTH / 2).into_bytes();
+        bytes.extend(b"\0\xE9");
+        assert_eq!(detect(&bytes), Some(Encoding::Utf8));
+
+        let mut bytes = b"a".repeat(SNIFF_LENGTH - 1);
+        bytes.extend("é".as_bytes());
+        assert_eq!(detect(&bytes), Some(Encoding::Utf8));
+
+        let mut bytes = b"caf\xE9 ".repeat(SNIFF_LENGTH);
+        bytes.push(0x1B);
+        assert_eq!(detect(&bytes), Some(Encoding::Latin1));
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(Encoding::Utf8.decode(b"\xEF\xBB\xBFhello"), "hello");
+        assert_eq!(Encoding::Utf16Le.decode(b"\xFF\xFEh\0\xE9\0"), "hé");
+        assert_eq!(Encoding::Utf16Be.decode(b"\0h\0\xE9"), "hé");
+        assert_eq!(Encoding::Latin1.decode(b"caf\xE9"), "café");
+        assert_eq!(
+            Encoding::Latin1.decode(b"it\x92s \x93quoted\x94 \x96 \x81"),
+            "it’s “quoted” – \u{81}"
+        );
+    }
+}
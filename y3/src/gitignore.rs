@@ -0,0 +1,306 @@
+//!
+//! # Gitignore
+//!
+//! Git compatible matching of ignore files, like `.gitignore`, `.git/info/exclude`
+//! and the global excludes file (`core.excludesFile`)
+//!
+//! ## Pattern Format
+//!
+//! - Blank lines and lines starting with `#` are skipped, `\#` matches a literal `#`
+//! - Trailing spaces are ignored, unless escaped with `\`
+//! - `!` negates the pattern, re-including paths excluded by a previous pattern
+//! - A trailing `/` only matches directories
+//! - Patterns containing a `/` (other than the trailing one) are anchored to the
+//!   directory of the ignore file, others match at any depth
+//! - `*`, `?` and `[...]` never match `/`, while `**` matches across directories
+//!
+//! ## Considerations
+//!
+//! - The last matching pattern decides if a path is ignored
+//! - Invalid patterns are skipped, same as git does
+//!
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+///
+/// Result of matching a path against a [Gitignore]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Match {
+    ///
+    /// No pattern matched the path
+    ///
+    None,
+
+    ///
+    /// Path is ignored
+    ///
+    Ignore,
+
+    ///
+    /// Path is explicitly re-included by a negated (`!`) pattern
+    ///
+    Whitelist,
+}
+
+///
+/// A single pattern of the ignore file
+///
+#[derive(Debug)]
+struct Rule {
+    ///
+    /// Pattern re-includes matching paths (`!pattern`)
+    ///
+    negated: bool,
+
+    ///
+    /// Pattern only matches directories (`pattern/`)
+    ///
+    dir_only: bool,
+}
+
+///
+/// Patterns of a single ignore file
+///
+#[derive(Debug)]
+pub struct Gitignore {
+    ///
+    /// Directory the patterns are relative to
+    ///
+    root: PathBuf,
+
+    ///
+    /// Compiled globs of all the [Rule]'s, in the same order
+    ///
+    globs: GlobSet,
+
+    ///
+    /// Rules of the ignore file, in the same order as written
+    ///
+    rules: Vec<Rule>,
+}
+
+impl Gitignore {
+    ///
+    /// Load the ignore file at [path]
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Directory the patterns are relative to
+    /// * `path` - Path of the ignore file
+    ///
+    pub fn load<P: AsRef<Path>, Q: AsRef<Path>>(root: P, path: Q) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+
+        Self::parse(root, &content)
+    }
+
+    ///
+    /// Parse the content of an ignore file
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Directory the patterns are relative to
+    /// * `content` - Content of the ignore file
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::gitignore::{Gitignore, Match};
+    ///
+    /// let gitignore = Gitignore::parse("", "target/\n*.log\n!keep.log").unwrap();
+    ///
+    /// assert_eq!(gitignore.matched("src/target", true), Match::Ignore);
+    /// assert_eq!(gitignore.matched("src/target", false), Match::None);
+    /// assert_eq!(gitignore.matched("debug.log", false), Match::Ignore);
+    /// assert_eq!(gitignore.matched("logs/keep.log", false), Match::Whitelist);
+    /// ```
+    ///
+    pub fn parse<P: AsRef<Path>>(root: P, content: &str) -> io::Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        let mut rules = Vec::new();
+
+        for line in content.lines() {
+            let Some((glob, rule)) = Self::parse_line(line) else {
+                continue;
+            };
+
+            let Ok(glob) = GlobBuilder::new(&glob)
+                .literal_separator(true)
+                .backslash_escape(true)
+                .build()
+            else {
+                continue;
+            };
+
+            builder.add(glob);
+            rules.push(rule);
+        }
+
+        let globs = builder.build().map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Failed to build glob set: {err}"),
+            )
+        })?;
+
+        Ok(Self {
+            root: root.as_ref().to_path_buf(),
+            globs,
+            rules,
+        })
+    }
+
+    ///
+    /// Getter to read the directory the patterns are relative to
+    ///
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    ///
+    /// Check if the ignore file has no patterns
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    ///
+    /// Match the [path] against the patterns, the last matching pattern wins
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to match, relative to [Gitignore::root] or prefixed by it
+    /// * `is_dir` - Path is a directory
+    ///
+    pub fn matched<P: AsRef<Path>>(&self, path: P, is_dir: bool) -> Match {
+        let path = path.as_ref();
+        let path = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative,
+            // Absolute paths outside of the root can't match
+            Err(_) if path.is_absolute() => return Match::None,
+            Err(_) => path,
+        };
+
+        if path.as_os_str().is_empty() {
+            return Match::None;
+        }
+
+        self.globs
+            .matches(path)
+            .into_iter()
+            .rev()
+            .map(|idx| &self.rules[idx])
+            .find(|rule| is_dir || !rule.dir_only)
+            .map_or(Match::None, |rule| match rule.negated {
+                true => Match::Whitelist,
+                false => Match::Ignore,
+            })
+    }
+
+    ///
+    /// Convert a line of the ignore file into a glob and its [Rule]
+    ///
+    fn parse_line(line: &str) -> Option<(String, Rule)> {
+        let mut line = line.trim_end_matches('\r');
+
+        // Trailing spaces are ignored unless escaped
+        while line.ends_with(' ') && !line.ends_with("\\ ") {
+            line = &line[..line.len() - 1];
+        }
+
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let negated = line.starts_with('!');
+        if negated {
+            line = &line[1..];
+        }
+
+        let dir_only = line.ends_with('/');
+        if dir_only {
+            line = &line[..line.len() - 1];
+        }
+
+        if line.is_empty() {
+            return None;
+        }
+
+        // Patterns with a separator are relative to the root, others match at
+        // any depth
+        let glob = match line.contains('/') {
+            true => line.strip_prefix('/').unwrap_or(line).to_string(),
+            false => format!("**/{line}"),
+        };
+
+        Some((glob, Rule { negated, dir_only }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unanchored_patterns() {
+        let gitignore = Gitignore::parse("", "target\n*.rlib").unwrap();
+
+        assert_eq!(gitignore.matched("target", true), Match::Ignore);
+        assert_eq!(gitignore.matched("crates/y3/target", true), Match::Ignore);
+        assert_eq!(gitignore.matched("crates/lib.rlib", false), Match::Ignore);
+        assert_eq!(gitignore.matched("targets", true), Match::None);
+    }
+
+    #[test]
+    fn test_anchored_patterns() {
+        let gitignore = Gitignore::parse("", "/build\ndocs/*.html\nlogs/**/debug").unwrap();
+
+        assert_eq!(gitignore.matched("build", true), Match::Ignore);
+        assert_eq!(gitignore.matched("src/build", true), Match::None);
+        assert_eq!(gitignore.matched("docs/index.html", false), Match::Ignore);
+        assert_eq!(gitignore.matched("docs/api/index.html", false), Match::None);
+        assert_eq!(gitignore.matched("logs/debug", false), Match::Ignore);
+        assert_eq!(gitignore.matched("logs/a/b/debug", false), Match::Ignore);
+    }
+
+    #[test]
+    fn test_negation_and_dir_only() {
+        let gitignore = Gitignore::parse("", "*.log\n!important.log\nout/").unwrap();
+
+        assert_eq!(gitignore.matched("debug.log", false), Match::Ignore);
+        assert_eq!(gitignore.matched("important.log", false), Match::Whitelist);
+        assert_eq!(gitignore.matched("out", true), Match::Ignore);
+        assert_eq!(gitignore.matched("out", false), Match::None);
+    }
+
+    #[test]
+    fn test_escaped_characters() {
+        let gitignore = Gitignore::parse(
+            "",
+            "\\#notes\n\\!important\ntrailing\\ \nspaces   \n# comment",
+        )
+        .unwrap();
+
+        assert_eq!(gitignore.matched("#notes", false), Match::Ignore);
+        assert_eq!(gitignore.matched("!important", false), Match::Ignore);
+        assert_eq!(gitignore.matched("trailing ", false), Match::Ignore);
+        assert_eq!(gitignore.matched("spaces", false), Match::Ignore);
+        assert_eq!(gitignore.matched("# comment", false), Match::None);
+    }
+
+    #[test]
+    fn test_root_prefix() {
+        let gitignore = Gitignore::parse("/repo/sub", "/generated").unwrap();
+
+        assert_eq!(
+            gitignore.matched("/repo/sub/generated", true),
+            Match::Ignore
+        );
+        assert_eq!(gitignore.matched("/repo/generated", true), Match::None);
+    }
+}
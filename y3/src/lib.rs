@@ -1,2 +1,10 @@
+pub mod baseline;
+pub mod checker;
+pub mod config;
+pub mod dictionary;
+pub mod diff;
+pub mod encoding;
+pub mod entropy;
+pub mod gitignore;
+pub mod reader;
 pub mod tokenizer;
-pub mod reader;
\ No newline at end of file
@@ -1,56 +1,644 @@
-use std::io;
-use y3::reader::Reader;
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread,
+};
+use y3::{
+    baseline::Baseline,
+    checker::{Checker, Misspelling},
+    config::{Config, OutputFormat, Severity, DEFAULT_LANGUAGE},
+    dictionary::Dictionary,
+    diff::ChangedLines,
+    reader::Reader,
+    tokenizer::{Digits, Tokenizer},
+};
 
-fn main() -> io::Result<()> {
-    let args: Vec<String> = std::env::args().collect();
+///
+/// Source of the diff used to limit checks to changed lines
+///
+enum DiffSource {
+    ///
+    /// Run `git diff`, optionally against a revision and/or the staging area
+    ///
+    Git { rev: Option<String>, staged: bool },
 
-    if args.len() < 2 {
+    ///
+    /// Read a unified diff from stdin
+    ///
+    Stdin,
+}
+
+///
+/// Path or stdin to be checked, in the order given on the command line
+///
+enum Input {
+    Path(PathBuf),
+    Stdin,
+}
+
+///
+/// Options controlling which files are collected from directories
+///
+struct WalkOptions {
+    jobs: usize,
+    max_file_size: Option<u64>,
+    follow_symlinks: bool,
+    one_file_system: bool,
+    no_ignore: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    file_types: Vec<String>,
+}
+
+fn main() -> io::Result<ExitCode> {
+    let mut inputs: Vec<Input> = Vec::new();
+    let mut stdin_filename: Option<PathBuf> = None;
+    let mut diff_rev: Option<String> = None;
+    let mut staged = false;
+    let mut baseline_path: Option<PathBuf> = None;
+    let mut write_baseline_path: Option<PathBuf> = None;
+    let mut config_path: Option<PathBuf> = None;
+    let mut no_ignore = false;
+    let mut jobs: Option<usize> = None;
+    let mut max_file_size: Option<u64> = None;
+    let mut min_word_length: Option<usize> = None;
+    let mut digits: Option<Digits> = None;
+    let mut follow_symlinks = false;
+    let mut one_file_system = false;
+    let mut include: Vec<String> = Vec::new();
+    let mut exclude: Vec<String> = Vec::new();
+    let mut file_types: Vec<String> = Vec::new();
+
+    // Paths may not be valid UTF-8, so arguments are kept as [OsString]'s
+    let mut args = std::env::args_os().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.to_str().unwrap_or_default() {
+            "--diff" => match args.next().and_then(|rev| rev.into_string().ok()) {
+                Some(rev) => diff_rev = Some(rev),
+                None => return Ok(usage_error("--diff requires a revision or `-`")),
+            },
+            "--staged" => staged = true,
+            "--baseline" => match args.next() {
+                Some(path) => baseline_path = Some(path.into()),
+                None => return Ok(usage_error("--baseline requires a file path")),
+            },
+            "--write-baseline" => match args.next() {
+                Some(path) => write_baseline_path = Some(path.into()),
+                None => return Ok(usage_error("--write-baseline requires a file path")),
+            },
+            "--config" => match args.next() {
+                Some(path) => config_path = Some(path.into()),
+                None => return Ok(usage_error("--config requires a file path")),
+            },
+            "--no-ignore" => no_ignore = true,
+            "--include" => match args.next().and_then(|glob| glob.into_string().ok()) {
+                Some(glob) => include.push(glob),
+                None => return Ok(usage_error("--include requires a glob")),
+            },
+            "--exclude" => match args.next().and_then(|glob| glob.into_string().ok()) {
+                Some(glob) => exclude.push(glob),
+                None => return Ok(usage_error("--exclude requires a glob")),
+            },
+            "-t" | "--type" => match args.next().and_then(|types| types.into_string().ok()) {
+                Some(types) => file_types.extend(types.split(',').map(str::to_string)),
+                None => return Ok(usage_error("--type requires a list of file types")),
+            },
+            "-L" | "--follow-symlinks" => follow_symlinks = true,
+            "--one-file-system" => one_file_system = true,
+            "-j" | "--jobs" => match args.next().and_then(|n| n.to_str()?.parse().ok()) {
+                Some(n) if n > 0 => jobs = Some(n),
+                _ => return Ok(usage_error("--jobs requires a positive number")),
+            },
+            "--max-file-size" => match args.next().and_then(|n| n.to_str()?.parse().ok()) {
+                Some(n) => max_file_size = Some(n),
+                None => return Ok(usage_error("--max-file-size requires a size in bytes")),
+            },
+            "--min-word-length" => match args.next().and_then(|n| n.to_str()?.parse().ok()) {
+                Some(n) => min_word_length = Some(n),
+                None => return Ok(usage_error("--min-word-length requires a number")),
+            },
+            "--digits" => match args
+                .next()
+                .and_then(|mode| Digits::from_name(mode.to_str()?))
+            {
+                Some(mode) => digits = Some(mode),
+                None => return Ok(usage_error("--digits requires `keep`, `strip` or `skip`")),
+            },
+            "--stdin-filename" => match args.next() {
+                Some(path) => stdin_filename = Some(path.into()),
+                None => return Ok(usage_error("--stdin-filename requires a file path")),
+            },
+            "-h" | "--help" => {
+                print_help();
+                return Ok(ExitCode::SUCCESS);
+            }
+            "-" => inputs.push(Input::Stdin),
+            // Everything after `--` is a path, even when it starts with `-`
+            "--" => {
+                for arg in args.by_ref() {
+                    match arg.to_str() {
+                        Some("-") => inputs.push(Input::Stdin),
+                        _ => inputs.push(Input::Path(arg.into())),
+                    }
+                }
+            }
+            option if option.starts_with('-') => {
+                return Ok(usage_error(&format!("Unknown option `{option}`")));
+            }
+            _ => inputs.push(Input::Path(arg.into())),
+        }
+    }
+
+    if inputs.is_empty() {
         print_help();
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
-    let dir_path = &args[1];
+    let reads_stdin = inputs.iter().any(|input| matches!(input, Input::Stdin));
+    if reads_stdin && diff_rev.as_deref() == Some("-") {
+        return Ok(usage_error(
+            "stdin can't be used for both `-` and `--diff -`",
+        ));
+    }
 
-    let mut reader = Reader::new(dir_path);
+    // Config and diff are looked up from every path, stdin alone is looked up
+    // from the current directory
+    let mut paths: Vec<&Path> = inputs
+        .iter()
+        .filter_map(|input| match input {
+            Input::Path(path) => Some(path.as_path()),
+            Input::Stdin => None,
+        })
+        .collect();
+    if paths.is_empty() {
+        paths.push(Path::new("."));
+    }
+
+    let config = match config_path {
+        Some(ref path) => Config::load(path)?,
+        None => match discover_config(&paths) {
+            Ok(config) => config,
+            Err(err) if err.kind() == io::ErrorKind::InvalidInput => {
+                return Ok(usage_error(&err.to_string()))
+            }
+            Err(err) => return Err(err),
+        },
+    };
+
+    let diff_source = match diff_rev {
+        Some(rev) if rev == "-" => Some(DiffSource::Stdin),
+        rev if rev.is_some() || staged => Some(DiffSource::Git { rev, staged }),
+        _ => None,
+    };
 
-    reader.get_files(dir_path)?;
+    let changed_lines = match diff_source {
+        Some(source) => Some(load_changed_lines(&paths, source)?),
+        None => None,
+    };
 
-    println!("Fetched {} files!", reader.paths().len());
+    let mut baseline = match baseline_path {
+        Some(ref path) => Some(Baseline::load(path)?),
+        None => None,
+    };
+    let mut new_baseline = match write_baseline_path {
+        Some(ref path) => Some(Baseline::for_file(path)?),
+        None => None,
+    };
 
-    println!("----------");
+    let jobs = jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from));
 
-    for p in reader.paths() {
-        println!("{:?}", p);
+    let options = WalkOptions {
+        jobs,
+        max_file_size: max_file_size.or(config.max_file_size()),
+        follow_symlinks,
+        one_file_system,
+        no_ignore,
+        include,
+        exclude,
+        file_types,
+    };
+
+    let dictionaries = load_dictionaries(&config)?;
+
+    let mut builder = Tokenizer::builder()
+        .ignore_random_strings(config.ignore_random_strings())
+        .hyphenated_compounds(config.hyphenated_compounds())
+        .digits(digits.unwrap_or(config.digits()));
+    if let Some(len) = min_word_length.or(config.min_word_length()) {
+        builder = builder.min_word_length(len);
+    }
+    for pattern in config.ignore_patterns() {
+        builder = builder.ignore_pattern(pattern.clone());
     }
+    for pattern in config.line_ignore_patterns() {
+        builder = builder.line_ignore_pattern(pattern.clone());
+    }
+    for pattern in config.disabled_builtin_patterns() {
+        builder = builder.disable_builtin_pattern(*pattern);
+    }
+
+    let mut checker =
+        Checker::with_tokenizer(&dictionaries[config.default_language()], builder.build());
+    checker.set_compound_words(config.compound_words());
+
+    // Files are checked while the inputs are still walked, the index of the
+    // input of every file is kept to print findings in the order of the inputs
+    let mut file_inputs: Vec<usize> = Vec::new();
+    let mut seen = HashSet::new();
+
+    let walked = checker.check_stream(
+        jobs,
+        |found| {
+            for (input_idx, input) in inputs.iter().enumerate() {
+                let Input::Path(dir_path) = input else {
+                    continue;
+                };
+
+                walk_files(dir_path, &config, &options, |path| {
+                    // Unchanged files don't need to be checked at all
+                    if changed_lines
+                        .as_ref()
+                        .is_some_and(|changed| changed.lines(&path).is_none())
+                    {
+                        return;
+                    }
 
-    // if !Path::new(file_path).exists() {
-    //     eprintln!("[Error] File not found - {}", file_path);
-    //     return Ok(());
-    // }
+                    // Paths given several times, or nested in each other, are checked once
+                    if seen.insert(path.clone()) {
+                        file_inputs.push(input_idx);
+                        found(path);
+                    }
+                })?;
+            }
 
-    // let mut tokenizer = Tokenizer::new();
+            Ok(())
+        },
+        |path, checker| {
+            checker.set_dictionary(&dictionaries[config.language(path)]);
+            checker.set_encoding(config.encoding(path));
+        },
+    );
 
-    // tokenizer.tokenize(file_path)?;
+    let mut results: Vec<(usize, PathBuf, io::Result<Vec<Misspelling>>)> = match walked {
+        Ok(results) => file_inputs
+            .into_iter()
+            .zip(results)
+            .map(|(input_idx, (path, result))| (input_idx, path, result))
+            .collect(),
+        Err(err) if err.kind() == io::ErrorKind::InvalidInput => {
+            return Ok(usage_error(&err.to_string()))
+        }
+        Err(err) => return Err(err),
+    };
 
-    // for token in tokenizer.tokens() {
-    //     println!("{:?}", token);
-    // }
+    // Stdin takes the position of the first `-` among the inputs
+    if let Some(input_idx) = inputs
+        .iter()
+        .position(|input| matches!(input, Input::Stdin))
+    {
+        let path = stdin_filename.unwrap_or_else(|| PathBuf::from("<stdin>"));
+
+        let mut checker = checker.clone();
+        checker.set_dictionary(&dictionaries[config.language(&path)]);
+        checker.set_encoding(config.encoding(&path));
+        let result = checker.check_reader(io::stdin().lock(), &path);
+
+        results.push((input_idx, path, result));
+    }
+
+    // Files are found in any order by the parallel walk, findings are printed in
+    // the order of the inputs and sorted by path within each of them
+    results.sort_by(|(a_idx, a_path, _), (b_idx, b_path, _)| {
+        a_idx.cmp(b_idx).then_with(|| a_path.cmp(b_path))
+    });
+
+    let mut found = 0;
+
+    for (_, path, result) in results {
+        let lines = match changed_lines {
+            Some(ref changed) => match changed.lines(&path) {
+                Some(lines) => Some(lines),
+                None => continue, // stdin was not changed
+            },
+            None => None,
+        };
+
+        for misspelling in result? {
+            let position = misspelling.position();
+
+            if lines.is_some_and(|lines| !lines.contains(&position.line_no())) {
+                continue;
+            }
+
+            if let Some(ref mut new_baseline) = new_baseline {
+                new_baseline.insert(&misspelling);
+                continue;
+            }
+
+            if let Some(ref mut baseline) = baseline {
+                if baseline.suppress(&misspelling) {
+                    continue;
+                }
+            }
+
+            print_misspelling(&misspelling, config.format(), config.severity());
+            found += 1;
+        }
+    }
+
+    if let (Some(new_baseline), Some(path)) = (new_baseline, write_baseline_path) {
+        new_baseline.save(&path)?;
+        println!(
+            "Wrote {} findings to {}",
+            new_baseline.len(),
+            path.display()
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if found > 0 {
+        eprintln!("Found {found} misspelled words!");
+
+        if config.severity() == Severity::Error {
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+///
+/// Discover the config shared by all [paths]
+///
+/// Dictionaries, patterns and ignores of a config apply to the whole run, so paths
+/// with different configs, or with and without one, have to be checked separately.
+///
+fn discover_config(paths: &[&Path]) -> io::Result<Config> {
+    let mut shared: Option<(&Path, Option<Config>)> = None;
+
+    for &path in paths {
+        let config = Config::discover(path)?;
+
+        let Some((first_path, ref first)) = shared else {
+            shared = Some((path, config));
+            continue;
+        };
+
+        let root =
+            |config: &Option<Config>| config.as_ref().map(|config| config.root().to_path_buf());
+        if root(first) != root(&config) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} and {} use different configs, check them separately or pass --config",
+                    first_path.display(),
+                    path.display()
+                ),
+            ));
+        }
+    }
+
+    Ok(shared.and_then(|(_, config)| config).unwrap_or_default())
+}
+
+///
+/// Load a [Dictionary] for every language used by the [config]
+///
+fn load_dictionaries(config: &Config) -> io::Result<HashMap<String, Dictionary>> {
+    let mut dictionaries = HashMap::new();
+
+    for language in config.languages() {
+        if dictionaries.contains_key(language) {
+            continue;
+        }
+
+        let mut dictionary = match language {
+            DEFAULT_LANGUAGE => Dictionary::en_us(),
+            _ => Dictionary::new(),
+        };
+        dictionary.set_case_sensitivity(config.case_sensitivity());
+
+        for path in config.dictionaries().get(language).into_iter().flatten() {
+            dictionary.load(path).map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("Failed to load dictionary {}: {err}", path.display()),
+                )
+            })?;
+        }
+
+        if dictionary.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("No dictionary for language `{language}`"),
+            ));
+        }
+
+        dictionaries.insert(language.to_string(), dictionary);
+    }
+
+    Ok(dictionaries)
+}
+
+///
+/// Print the [misspelling] in the [format]
+///
+fn print_misspelling(misspelling: &Misspelling, format: OutputFormat, severity: Severity) {
+    let position = misspelling.position();
+
+    match format {
+        OutputFormat::Text => println!(
+            "{}:{}:{}: {severity}: {}",
+            misspelling.path().display(),
+            position.line_no(),
+            position.column() + 1,
+            misspelling.word()
+        ),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::json!({
+                "path": misspelling.path().to_string_lossy(),
+                "line": position.line_no(),
+                "column": position.column() + 1,
+                "word": misspelling.word(),
+                "severity": severity.to_string(),
+            })
+        ),
+    }
+}
+
+///
+/// Pass the files to check from [dir_path], a directory or a single file, to
+/// [callback] as soon as they are found
+///
+fn walk_files<F: FnMut(PathBuf)>(
+    dir_path: &Path,
+    config: &Config,
+    options: &WalkOptions,
+    callback: F,
+) -> io::Result<()> {
+    let mut reader = Reader::new(dir_path);
+    reader.set_jobs(options.jobs);
+    reader.set_max_file_size(options.max_file_size);
+    reader.set_follow_symlinks(options.follow_symlinks);
+    reader.set_same_file_system(options.one_file_system);
+
+    if !options.no_ignore {
+        reader.load_gitignore()?;
+    }
+
+    if !config.ignore().is_empty() {
+        reader.add_ignore_globs(config.root(), config.ignore())?;
+    }
+
+    add_filters(
+        &mut reader,
+        &options.include,
+        &options.exclude,
+        &options.file_types,
+    )?;
+
+    reader.for_each_file(dir_path, callback)?;
+
+    Ok(())
+}
+
+///
+/// Narrow down the files collected by the [reader] with the `--include`,
+/// `--exclude` and `--type` options
+///
+fn add_filters(
+    reader: &mut Reader,
+    include: &[String],
+    exclude: &[String],
+    file_types: &[String],
+) -> io::Result<()> {
+    if !include.is_empty() {
+        reader.add_include_globs(include)?;
+    }
+
+    if !exclude.is_empty() {
+        reader.add_exclude_globs(exclude)?;
+    }
+
+    if !file_types.is_empty() {
+        reader.add_file_types(file_types)?;
+    }
 
     Ok(())
 }
 
+///
+/// Collect lines changed by the diff from [source]
+///
+/// `git diff` runs in the repository of each of the [paths], which may differ
+///
+fn load_changed_lines(paths: &[&Path], source: DiffSource) -> io::Result<ChangedLines> {
+    match source {
+        DiffSource::Git { rev, staged } => {
+            let mut changed = ChangedLines::default();
+            let mut dirs = HashSet::new();
+
+            for &path in paths {
+                // `git -C` requires a directory
+                let dir = match path.is_file() {
+                    true => path.parent().unwrap_or(Path::new(".")),
+                    false => path,
+                };
+                let dir = match dir.as_os_str().is_empty() {
+                    true => Path::new("."),
+                    false => dir,
+                };
+
+                if dirs.insert(dir) {
+                    changed.merge(ChangedLines::from_git(dir, rev.as_deref(), staged)?);
+                }
+            }
+
+            Ok(changed)
+        }
+        DiffSource::Stdin => {
+            let mut diff = String::new();
+            io::stdin().read_to_string(&mut diff)?;
+
+            // Paths in the diff are relative to the current directory
+            Ok(ChangedLines::parse(&diff, "."))
+        }
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("[Error] {message}");
+    print_help();
+
+    ExitCode::from(2)
+}
+
 fn print_help() {
     const TEXT: &str = r#"
     Usage:
-        y3 <file_path>
+        y3 [options] [--] <path>...
 
     Description:
 
-    This program reads a file, extracts words, and prints each word along with its position.
+    This program reads files from the paths, or text from stdin when a path is
+    `-`, extracts words, and prints each misspelled word along with its position.
+    Arguments after `--` are paths, even when they start with `-`.
+
+    Options:
+
+        --diff <rev>    Only check lines added or modified since <rev> (uses `git diff`),
+                        use `-` to read a unified diff from stdin
+        --staged        Only check lines added or modified in staged changes
+        --baseline <file>
+                        Only report findings which are not part of the baseline <file>
+        --write-baseline <file>
+                        Write current findings to the baseline <file>
+        --config <file> Use the config <file> instead of discovering `y3.toml`
+                        (or `pyproject.toml` with a `[tool.y3]` section) from <path>
+                        and its parent directories
+        -j, --jobs <n>  Number of threads used to walk and check files, defaults to
+                        the number of available CPUs
+        --max-file-size <bytes>
+                        Skip files larger than <bytes>
+        --min-word-length <n>
+                        Skip words shorter than <n> characters, defaults to 2
+        --digits <mode> Check words with digits like `utf8` as they are (`keep`),
+                        with trailing digits stripped (`strip`) or skip them (`skip`)
+        -L, --follow-symlinks
+                        Walk into symlinked directories. Either way, each file is
+                        only checked once, under its own path when inside <path>
+        --one-file-system
+                        Don't walk into directories on other file systems
+        --include <glob>
+                        Only check files matching <glob>, relative to <path>. Globs
+                        without a `/` match at any depth, e.g. `*.md`
+        --exclude <glob>
+                        Skip files matching <glob>, e.g. `vendor/**` or `*.lock`
+        -t, --type <types>
+                        Only check files of the comma separated <types>, e.g.
+                        `rust,markdown`. Known types are c, cpp, css, go, html,
+                        java, js, json, kotlin, markdown, python, rst, ruby, rust,
+                        shell, tex, text, toml, ts and yaml
+        --no-ignore     Don't skip files matched by `.gitignore`, `.y3ignore`,
+                        `.git/info/exclude` or the global git excludes file
+        --stdin-filename <path>
+                        Path used for text read from stdin (`-`), selects its
+                        language and encoding, and is printed with findings
+        -h, --help      Print this help
 
     Example:
-    
+
     y3 dummy_text.txt
+    y3 --diff main .
+    git diff main | y3 --diff - .
+    y3 --write-baseline y3-baseline.txt .
+    y3 --baseline y3-baseline.txt .
+    y3 src docs README.md
+    git show :README.md | y3 --stdin-filename README.md -
 
     "#;
 
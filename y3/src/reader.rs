@@ -1,43 +1,335 @@
+use crate::gitignore::{Gitignore, Match};
 use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use std::{
-    fs, io,
+    collections::HashSet,
+    env, fs, io,
     path::{Path, PathBuf},
+    process::Command,
+    sync::{mpsc, Arc, Condvar, Mutex},
+    thread,
 };
 
+///
+/// Ignore files loaded from every directory, later ones take precedence
+///
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".y3ignore"];
+
+///
+/// Globs of the file types which can be selected with [Reader::add_file_types]
+///
+pub const FILE_TYPES: &[(&str, &[&str])] = &[
+    ("c", &["*.c", "*.h"]),
+    (
+        "cpp",
+        &["*.cpp", "*.cc", "*.cxx", "*.hpp", "*.hh", "*.hxx", "*.h"],
+    ),
+    ("css", &["*.css", "*.scss", "*.sass", "*.less"]),
+    ("go", &["*.go"]),
+    ("html", &["*.html", "*.htm"]),
+    ("java", &["*.java"]),
+    ("js", &["*.js", "*.jsx", "*.mjs", "*.cjs"]),
+    ("json", &["*.json"]),
+    ("kotlin", &["*.kt", "*.kts"]),
+    ("markdown", &["*.md", "*.markdown", "*.mdx"]),
+    ("python", &["*.py", "*.pyi"]),
+    ("rst", &["*.rst"]),
+    ("ruby", &["*.rb"]),
+    ("rust", &["*.rs"]),
+    ("shell", &["*.sh", "*.bash", "*.zsh"]),
+    ("tex", &["*.tex"]),
+    ("text", &["*.txt"]),
+    ("toml", &["*.toml"]),
+    ("ts", &["*.ts", "*.tsx"]),
+    ("yaml", &["*.yml", "*.yaml"]),
+];
+
+///
+/// Number of found files buffered between the walking threads and the callback
+/// of [Reader::for_each_file]
+///
+const CHANNEL_CAPACITY: usize = 1024;
+
+///
+/// A directory waiting to be walked, along with the ignore files applying to it
+///
+struct PendingDir {
+    path: PathBuf,
+    gitignores: Vec<Arc<Gitignore>>,
+
+    ///
+    /// Whether the directory lies outside of the walked one, reached through a
+    /// symlink
+    ///
+    is_outside: bool,
+}
+
+///
+/// Identity of a file or directory, shared by every path linking to it
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FileId {
+    #[cfg(unix)]
+    Inode { device: u64, inode: u64 },
+
+    #[cfg(not(unix))]
+    Path(PathBuf),
+}
+
+impl FileId {
+    ///
+    /// Identify the file at [path], device and inode numbers are used when
+    /// available, the canonical path otherwise
+    ///
+    fn of(path: &Path, metadata: &fs::Metadata) -> Option<Self> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            let _ = path;
+            Some(Self::Inode {
+                device: metadata.dev(),
+                inode: metadata.ino(),
+            })
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = metadata;
+            path.canonicalize().ok().map(Self::Path)
+        }
+    }
+
+    ///
+    /// Device of the file, `None` where it's not available
+    ///
+    fn device(metadata: &fs::Metadata) -> Option<u64> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            Some(metadata.dev())
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = metadata;
+            None
+        }
+    }
+}
+
+///
+/// Read-only settings of a single walk
+///
+struct WalkContext {
+    ///
+    /// Canonical path of the walked directory
+    ///
+    root: PathBuf,
+
+    ///
+    /// Device of the walked directory, set when staying on one file system
+    ///
+    root_device: Option<u64>,
+
+    ///
+    /// Files and directories outside of the walked directory found so far,
+    /// along with the directories walked when following symlinks
+    ///
+    seen: Mutex<HashSet<FileId>>,
+}
+
+impl WalkContext {
+    ///
+    /// Mark the file or directory [id] as found, `false` if it already was
+    ///
+    fn claim(&self, id: FileId) -> bool {
+        self.seen
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(id)
+    }
+}
+
+///
+/// State shared between the workers of [Reader::for_each_file]
+///
+#[derive(Default)]
+struct WalkState {
+    ///
+    /// Directories waiting to be walked
+    ///
+    pending: Vec<PendingDir>,
+
+    ///
+    /// Number of directories being walked right now
+    ///
+    active: usize,
+
+    ///
+    /// First error hit by any of the workers, stops the walk
+    ///
+    error: Option<io::Error>,
+}
+
+///
+/// Marks a directory as being walked, until dropped
+///
+/// The counter is also decremented when walking panics, so the other workers
+/// don't wait for the directory forever.
+///
+struct ActiveDir<'s> {
+    state: &'s Mutex<WalkState>,
+    ready: &'s Condvar,
+}
+
+impl Drop for ActiveDir<'_> {
+    fn drop(&mut self) {
+        let mut guard = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        guard.active -= 1;
+
+        if thread::panicking() {
+            guard
+                .error
+                .get_or_insert_with(|| io::Error::other("Walking a directory panicked"));
+        }
+
+        self.ready.notify_all();
+    }
+}
+
 pub struct Reader {
-    paths: Vec<String>,
-    ignore_patterns: Option<GlobSet>,
+    paths: Vec<PathBuf>,
+    gitignores: Vec<Arc<Gitignore>>,
+    canonical_base_dir: Option<PathBuf>,
+    ignore_globs: Vec<(PathBuf, GlobSet)>,
+    include_patterns: Vec<String>,
+    include_globs: Option<GlobSet>,
     base_dir: PathBuf,
+    jobs: usize,
+    max_file_size: Option<u64>,
+    follow_symlinks: bool,
+    same_file_system: bool,
 }
 
 impl Reader {
-    pub fn new(base_dir: &str) -> Self {
+    pub fn new<P: AsRef<Path>>(base_dir: P) -> Self {
         Self {
             paths: Vec::new(),
-            ignore_patterns: None,
-            base_dir: PathBuf::from(base_dir),
+            gitignores: Vec::new(),
+            canonical_base_dir: None,
+            ignore_globs: Vec::new(),
+            include_patterns: Vec::new(),
+            include_globs: None,
+            base_dir: base_dir.as_ref().to_path_buf(),
+            jobs: 1,
+            max_file_size: None,
+            follow_symlinks: false,
+            same_file_system: false,
         }
     }
 
-    pub fn paths(&self) -> &[String] {
+    pub fn paths(&self) -> &[PathBuf] {
         &self.paths
     }
 
+    ///
+    /// Number of threads used to walk directories, `1` by default
+    ///
+    /// # Arguments
+    ///
+    /// * `jobs` - Number of threads, values below `1` are treated as `1`
+    ///
+    pub fn set_jobs(&mut self, jobs: usize) {
+        self.jobs = jobs.max(1);
+    }
+
+    ///
+    /// Skip files larger than [max_file_size] bytes, no limit by default
+    ///
+    pub fn set_max_file_size(&mut self, max_file_size: Option<u64>) {
+        self.max_file_size = max_file_size;
+    }
+
+    ///
+    /// Walk into symlinked directories, only symlinked files are checked by default
+    ///
+    /// Every directory is only walked once, so symlink cycles stop. Links into
+    /// the walked directory are always skipped, the target being found under its
+    /// own path. Files and directories outside of it are found once, through
+    /// whichever link is reached first.
+    ///
+    pub fn set_follow_symlinks(&mut self, follow_symlinks: bool) {
+        self.follow_symlinks = follow_symlinks;
+    }
+
+    ///
+    /// Don't walk into directories on a different file system than the walked
+    /// directory, e.g. mount points (only supported on unix)
+    ///
+    pub fn set_same_file_system(&mut self, same_file_system: bool) {
+        self.same_file_system = same_file_system;
+    }
+
     ///
     /// Extract file paths from the current directory and return the count
     ///
+    /// Found files are sorted so the order doesn't depend on the scheduling of
+    /// the walking threads.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the directory or a specific file
+    ///
+    pub fn get_files<P: AsRef<Path>>(&mut self, path: P) -> io::Result<usize> {
+        let mut paths = Vec::new();
+        let count = self.for_each_file(path, |path| paths.push(path))?;
+
+        paths.sort();
+        self.paths.append(&mut paths);
+
+        Ok(count)
+    }
+
+    ///
+    /// Walk the current directory and call [callback] with every file as soon as
+    /// it's found, then return the count
+    ///
+    /// Directories are walked by [Reader::set_jobs] threads, so files are passed
+    /// in no particular order. The [callback] always runs on the calling thread.
+    ///
     /// # Arguments
     ///
     /// * `path` - Path to the directory or a specific file
+    /// * `callback` - Called with the path of every file which isn't ignored
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::reader::Reader;
     ///
-    pub fn get_files(&mut self, path: &str) -> io::Result<usize> {
+    /// let reader = Reader::new("src");
+    /// let mut paths = Vec::new();
+    ///
+    /// let count = reader.for_each_file("src", |path| paths.push(path)).unwrap();
+    ///
+    /// assert_eq!(count, paths.len());
+    /// assert!(paths.iter().any(|path| path.ends_with("reader.rs")));
+    /// ```
+    ///
+    pub fn for_each_file<P, F>(&self, path: P, mut callback: F) -> io::Result<usize>
+    where
+        P: AsRef<Path>,
+        F: FnMut(PathBuf),
+    {
+        let path = path.as_ref();
         let metadata = fs::metadata(path)?;
 
         if metadata.is_file() {
-            if self.should_ignore(path) {
+            if self.is_too_large(&metadata) || self.should_ignore(path) {
                 return Ok(0);
             }
-            self.paths.push(path.to_string());
+            callback(path.to_path_buf());
             return Ok(1);
         }
 
@@ -47,22 +339,13 @@ impl Reader {
                 return Ok(0);
             }
 
-            let mut count = 0;
-            for entry in fs::read_dir(path)? {
-                let entry = entry?;
-                let entry_path = entry.path();
-                let entry_str = entry_path.to_str().unwrap_or_default();
-
-                if entry_path.is_dir() {
-                    count += self.get_files(entry_str)?;
-                } else if entry_path.is_file() {
-                    if !self.should_ignore(entry_str) {
-                        self.paths.push(entry_str.to_string());
-                        count += 1;
-                    }
-                }
-            }
-            return Ok(count);
+            let root = PendingDir {
+                path: path.to_path_buf(),
+                gitignores: self.gitignores.clone(),
+                is_outside: false,
+            };
+
+            return self.walk(root, &metadata, &mut callback);
         }
 
         // If the path is neither file nor directory, return an error
@@ -73,30 +356,396 @@ impl Reader {
     }
 
     ///
-    /// Parse .gitignore file and load ignore patterns
+    /// Load ignore patterns the same way git does, from
     ///
-    /// # Arguments
+    /// - The global excludes file (`core.excludesFile`, defaults to
+    ///   `$XDG_CONFIG_HOME/git/ignore`)
+    /// - `.git/info/exclude` of the repository
+    /// - `.gitignore` and `.y3ignore` files from the root of the repository down
+    ///   to the base directory
+    /// - Nested `.gitignore` and `.y3ignore` files, while walking through sub
+    ///   directories
     ///
-    /// * `gitignore_path` - Path to the .gitignore file
+    /// Patterns of deeper files take precedence, and `.y3ignore` patterns take
+    /// precedence over `.gitignore` patterns of the same directory.
     ///
     pub fn load_gitignore(&mut self) -> io::Result<()> {
-        let gitignore_path = self.base_dir.join(".gitignore");
-        if !gitignore_path.exists() {
-            return Ok(()); // No .gitignore file, nothing to do
+        let base_dir = self.base_dir.canonicalize()?;
+        let base_dir_path = match base_dir.is_dir() {
+            true => base_dir.as_path(),
+            false => base_dir.parent().unwrap_or(&base_dir),
+        };
+
+        let repo_root = base_dir_path
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(base_dir_path)
+            .to_path_buf();
+
+        if let Some(excludes_file) = Self::global_excludes_file(&repo_root) {
+            Self::add_gitignore(&mut self.gitignores, &repo_root, &excludes_file)?;
         }
 
-        let gitignore_content = fs::read_to_string(&gitignore_path)?;
-        let mut builder = GlobSetBuilder::new();
+        Self::add_gitignore(
+            &mut self.gitignores,
+            &repo_root,
+            &repo_root.join(".git/info/exclude"),
+        )?;
+
+        let mut dirs: Vec<&Path> = base_dir_path
+            .ancestors()
+            .take_while(|dir| dir.starts_with(&repo_root))
+            .collect();
+        dirs.reverse();
+
+        for dir in dirs {
+            Self::add_ignore_files(&mut self.gitignores, dir)?;
+        }
+
+        self.canonical_base_dir = Some(base_dir);
+
+        Ok(())
+    }
+
+    ///
+    /// Walk the directory [root] and everything below it, using [Reader::set_jobs]
+    /// threads pulling directories from a shared queue
+    ///
+    fn walk(
+        &self,
+        root: PendingDir,
+        metadata: &fs::Metadata,
+        callback: &mut dyn FnMut(PathBuf),
+    ) -> io::Result<usize> {
+        let context = WalkContext {
+            root: root.path.canonicalize()?,
+            root_device: FileId::device(metadata).filter(|_| self.same_file_system),
+            seen: Mutex::new(HashSet::new()),
+        };
+
+        // Links back to the walked directory are skipped as being inside of it,
+        // claiming it as well only matters for outside links cycling back
+        if let Some(id) = FileId::of(&root.path, metadata).filter(|_| self.follow_symlinks) {
+            context.claim(id);
+        }
+
+        let state = Mutex::new(WalkState {
+            pending: vec![root],
+            ..Default::default()
+        });
+        let ready = Condvar::new();
+        let mut count = 0;
+
+        let mut found = |path: PathBuf| {
+            count += 1;
+            callback(path);
+        };
+
+        match self.jobs {
+            1 => self.walk_worker(&state, &ready, &context, &mut found),
+            jobs => thread::scope(|scope| {
+                // Bounded, so a slow callback holds the walk back instead of
+                // buffering every path
+                let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+                let (state, ready, context) = (&state, &ready, &context);
+
+                for _ in 0..jobs {
+                    let sender = sender.clone();
+                    scope.spawn(move || {
+                        self.walk_worker(state, ready, context, &mut |path| {
+                            let _ = sender.send(path);
+                        })
+                    });
+                }
+                drop(sender);
+
+                for path in receiver {
+                    found(path);
+                }
+            }),
+        }
+
+        let state = state.into_inner().unwrap_or_else(|err| err.into_inner());
+        match state.error {
+            Some(err) => Err(err),
+            None => Ok(count),
+        }
+    }
+
+    ///
+    /// Walk pending directories until all of them are done or an error occurs,
+    /// passing found files to [emit]
+    ///
+    fn walk_worker(
+        &self,
+        state: &Mutex<WalkState>,
+        ready: &Condvar,
+        context: &WalkContext,
+        emit: &mut dyn FnMut(PathBuf),
+    ) {
+        loop {
+            let dir = {
+                let mut guard = state.lock().unwrap_or_else(|err| err.into_inner());
+
+                loop {
+                    if guard.error.is_some() {
+                        return;
+                    }
 
-        for line in gitignore_content.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue; // Skip empty lines and comments
+                    if let Some(dir) = guard.pending.pop() {
+                        guard.active += 1;
+                        break dir;
+                    }
+
+                    // Nothing left to walk, and nobody can add more
+                    if guard.active == 0 {
+                        return;
+                    }
+
+                    guard = ready.wait(guard).unwrap_or_else(|err| err.into_inner());
+                }
+            };
+
+            let active = ActiveDir { state, ready };
+            let result = self.read_dir(dir, context);
+
+            let files = {
+                let mut guard = state.lock().unwrap_or_else(|err| err.into_inner());
+
+                match result {
+                    Ok((files, mut dirs)) => {
+                        guard.pending.append(&mut dirs);
+                        files
+                    }
+                    Err(err) => {
+                        guard.error.get_or_insert(err);
+                        Vec::new()
+                    }
+                }
+            };
+
+            // Found directories are queued before the directory is done, so
+            // waiting workers see them
+            drop(active);
+
+            // Files are passed on outside of the lock, [emit] may block
+            for file in files {
+                emit(file);
+            }
+        }
+    }
+
+    ///
+    /// List the files and the sub directories of a single directory, skipping
+    /// ignored ones
+    ///
+    fn read_dir(
+        &self,
+        dir: PendingDir,
+        context: &WalkContext,
+    ) -> io::Result<(Vec<PathBuf>, Vec<PendingDir>)> {
+        let PendingDir {
+            path,
+            mut gitignores,
+            is_outside,
+        } = dir;
+
+        // Patterns of nested ignore files only apply inside of their directory
+        self.load_nested_gitignore(&path, &mut gitignores)?;
+
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+
+        for entry in fs::read_dir(&path)? {
+            let entry = entry?;
+            let entry_path = entry.path();
+
+            // Symlinked files are checked, symlinked directories are only walked
+            // when following symlinks, broken links are always skipped
+            let is_symlink = entry.file_type().is_ok_and(|kind| kind.is_symlink());
+            let Ok(metadata) = fs::metadata(&entry_path) else {
+                continue;
+            };
+
+            // Links into the walked directory are skipped, the target is found
+            // through its own path. Whatever lies outside of it may be reached
+            // through several links, and is only kept the first time.
+            let is_entry_outside = match is_symlink {
+                true => match entry_path.canonicalize() {
+                    Ok(target) if target.starts_with(&context.root) => continue,
+                    Ok(_) => true,
+                    Err(_) => continue,
+                },
+                false => is_outside,
+            };
+
+            if metadata.is_dir() {
+                // Ignored directories are skipped without being walked
+                if (self.follow_symlinks || !is_symlink)
+                    && !entry_path.ends_with(".git")
+                    && !self.should_ignore_dir(&gitignores, &entry_path)
+                    && context
+                        .root_device
+                        .map_or(true, |device| FileId::device(&metadata) == Some(device))
+                {
+                    // Directories are walked once, which also stops links
+                    // cycling back to a directory above
+                    if self.follow_symlinks
+                        && FileId::of(&entry_path, &metadata).is_some_and(|id| !context.claim(id))
+                    {
+                        continue;
+                    }
+
+                    dirs.push(PendingDir {
+                        path: entry_path,
+                        gitignores: gitignores.clone(),
+                        is_outside: is_entry_outside,
+                    });
+                }
+            } else if metadata.is_file()
+                && self.is_included(&entry_path)
+                && !self.is_too_large(&metadata)
+                && !self.should_ignore_with(&gitignores, &entry_path)
+            {
+                if is_entry_outside
+                    && FileId::of(&entry_path, &metadata).is_some_and(|id| !context.claim(id))
+                {
+                    continue;
+                }
+
+                files.push(entry_path);
             }
+        }
+
+        Ok((files, dirs))
+    }
+
+    ///
+    /// Ignore paths matching any of the [globs]
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Directory the globs are relative to, e.g. directory of the config
+    ///   file, which may be a parent of the base directory
+    /// * `globs` - List of glob patterns, e.g. `vendor/**`
+    ///
+    pub fn add_ignore_globs<P: AsRef<Path>>(
+        &mut self,
+        root: P,
+        globs: &[String],
+    ) -> io::Result<()> {
+        let glob_set = Self::build_glob_set(globs)?;
+
+        // Walked paths start with the base directory, so only the location of
+        // the base directory relative to the [root] is needed for matching
+        let base_dir = self.base_dir.canonicalize()?;
+        let prefix = base_dir
+            .strip_prefix(root.as_ref().canonicalize()?)
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        self.ignore_globs.push((prefix, glob_set));
+
+        Ok(())
+    }
 
-            // Convert each pattern to a full path relative to the base directory
-            let pattern = self.base_dir.join(trimmed);
-            let glob_pattern = GlobBuilder::new(pattern.to_str().unwrap_or_default())
+    ///
+    /// Skip files matching any of the [globs], relative to the base directory
+    ///
+    /// Globs without a `/` match at any depth, e.g. `*.lock` matches
+    /// `Cargo.lock` and `web/yarn.lock`.
+    ///
+    pub fn add_exclude_globs(&mut self, globs: &[String]) -> io::Result<()> {
+        let globs: Vec<String> = globs.iter().map(|glob| Self::unanchored(glob)).collect();
+        let base_dir = self.base_dir.clone();
+
+        self.add_ignore_globs(base_dir, &globs)
+    }
+
+    ///
+    /// Only collect files matching any of the included [globs], relative to the
+    /// base directory
+    ///
+    /// Globs without a `/` match at any depth, e.g. `*.md` matches `README.md`
+    /// and `docs/guide.md`. Directories are still walked, and a file passed
+    /// directly is always collected.
+    ///
+    pub fn add_include_globs(&mut self, globs: &[String]) -> io::Result<()> {
+        self.include_patterns
+            .extend(globs.iter().map(|glob| Self::unanchored(glob)));
+        self.include_globs = Some(Self::build_glob_set(&self.include_patterns)?);
+
+        Ok(())
+    }
+
+    ///
+    /// Only collect files of the given [FILE_TYPES], e.g. `rust` or `markdown`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use y3::reader::Reader;
+    ///
+    /// let mut reader = Reader::new("src");
+    /// reader.add_file_types(&["rust"]).unwrap();
+    /// reader.get_files("src").unwrap();
+    ///
+    /// assert!(reader.paths().iter().all(|path| path.extension().unwrap() == "rs"));
+    /// assert!(Reader::new("src").add_file_types(&["cobol"]).is_err());
+    /// ```
+    ///
+    pub fn add_file_types<S: AsRef<str>>(&mut self, file_types: &[S]) -> io::Result<()> {
+        let mut globs = Vec::new();
+
+        for file_type in file_types {
+            let file_type = file_type.as_ref();
+            let (_, type_globs) = FILE_TYPES
+                .iter()
+                .find(|(name, _)| *name == file_type)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Unknown file type `{file_type}`"),
+                    )
+                })?;
+
+            globs.extend(type_globs.iter().map(|glob| glob.to_string()));
+        }
+
+        self.add_include_globs(&globs)
+    }
+
+    ///
+    /// Check if a walked file matches the included globs, if any
+    ///
+    fn is_included(&self, path: &Path) -> bool {
+        let Some(ref include_globs) = self.include_globs else {
+            return true;
+        };
+
+        let relative = path.strip_prefix(&self.base_dir).unwrap_or(path);
+
+        include_globs.is_match(relative)
+    }
+
+    ///
+    /// Make [glob] match at any depth unless it contains a `/`, same as gitignore
+    ///
+    fn unanchored(glob: &str) -> String {
+        match glob.contains('/') {
+            true => glob.strip_prefix('/').unwrap_or(glob).to_string(),
+            false => format!("**/{glob}"),
+        }
+    }
+
+    ///
+    /// Compile the [globs] into a [GlobSet], `*` doesn't match `/`
+    ///
+    fn build_glob_set(globs: &[String]) -> io::Result<GlobSet> {
+        let mut builder = GlobSetBuilder::new();
+
+        for glob in globs {
+            let glob_pattern = GlobBuilder::new(glob)
                 .literal_separator(true)
                 .build()
                 .map_err(|err| {
@@ -108,14 +757,20 @@ impl Reader {
             builder.add(glob_pattern);
         }
 
-        self.ignore_patterns = Some(builder.build().map_err(|err| {
+        builder.build().map_err(|err| {
             io::Error::new(
                 io::ErrorKind::InvalidInput,
                 format!("Failed to build glob set: {err}"),
             )
-        })?);
+        })
+    }
 
-        Ok(())
+    ///
+    /// Check if a file is larger than [Reader::set_max_file_size]
+    ///
+    fn is_too_large(&self, metadata: &fs::Metadata) -> bool {
+        self.max_file_size
+            .is_some_and(|max_file_size| metadata.len() > max_file_size)
     }
 
     ///
@@ -125,11 +780,442 @@ impl Reader {
     ///
     /// * `path` - Path to check
     ///
-    fn should_ignore(&self, path: &str) -> bool {
-        if let Some(ref patterns) = self.ignore_patterns {
-            patterns.is_match(Path::new(path))
-        } else {
-            false
+    fn should_ignore(&self, path: &Path) -> bool {
+        self.should_ignore_with(&self.gitignores, path)
+    }
+
+    ///
+    /// Check if a file should be ignored based on the [gitignores] applying to
+    /// its directory
+    ///
+    fn should_ignore_with(&self, gitignores: &[Arc<Gitignore>], path: &Path) -> bool {
+        self.is_gitignored(gitignores, path, false) || self.matches_ignore_globs(path)
+    }
+
+    ///
+    /// Check if a directory should be skipped while walking
+    ///
+    /// # Arguments
+    ///
+    /// * `gitignores` - Ignore files applying to the parent directory
+    /// * `path` - Path to the directory
+    ///
+    fn should_ignore_dir(&self, gitignores: &[Arc<Gitignore>], path: &Path) -> bool {
+        self.is_gitignored(gitignores, path, true) || self.matches_ignore_globs(path)
+    }
+
+    ///
+    /// Check if a path matches any of the globs added with [Reader::add_ignore_globs]
+    ///
+    fn matches_ignore_globs(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.base_dir).unwrap_or(path);
+
+        self.ignore_globs.iter().any(|(prefix, patterns)| {
+            // Base directory is a file
+            if relative.as_os_str().is_empty() {
+                return patterns.is_match(prefix);
+            }
+
+            patterns.is_match(prefix.join(relative))
+        })
+    }
+
+    ///
+    /// Check if a path is ignored by the loaded ignore files
+    ///
+    /// Ignored directories are never walked, so same as git, paths inside of them
+    /// can't be re-included by a negated pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `gitignores` - Ignore files applying to the path
+    /// * `path` - Path to check
+    /// * `is_dir` - Path is a directory
+    ///
+    fn is_gitignored(&self, gitignores: &[Arc<Gitignore>], path: &Path, is_dir: bool) -> bool {
+        let Some(ref base_dir) = self.canonical_base_dir else {
+            return false;
+        };
+
+        let absolute = self.absolute_path(base_dir, path);
+
+        Self::gitignore_match(gitignores, &absolute, is_dir) == Match::Ignore
+    }
+
+    ///
+    /// Match the absolute [path] against the [gitignores], deeper files take
+    /// precedence
+    ///
+    fn gitignore_match(gitignores: &[Arc<Gitignore>], path: &Path, is_dir: bool) -> Match {
+        gitignores
+            .iter()
+            .rev()
+            .map(|gitignore| gitignore.matched(path, is_dir))
+            .find(|m| *m != Match::None)
+            .unwrap_or(Match::None)
+    }
+
+    ///
+    /// Load the ignore files of a sub directory reached while walking
+    ///
+    fn load_nested_gitignore(
+        &self,
+        path: &Path,
+        gitignores: &mut Vec<Arc<Gitignore>>,
+    ) -> io::Result<()> {
+        let Some(ref base_dir) = self.canonical_base_dir else {
+            return Ok(());
+        };
+
+        let dir = self.absolute_path(base_dir, path);
+
+        // Ignore files of the base directory are loaded with [Reader::load_gitignore]
+        if dir == *base_dir {
+            return Ok(());
+        }
+
+        Self::add_ignore_files(gitignores, &dir)
+    }
+
+    ///
+    /// Load the [IGNORE_FILES] of the directory [dir] into [gitignores]
+    ///
+    fn add_ignore_files(gitignores: &mut Vec<Arc<Gitignore>>, dir: &Path) -> io::Result<()> {
+        for file_name in IGNORE_FILES {
+            Self::add_gitignore(gitignores, dir, &dir.join(file_name))?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Load the ignore file at [path] into [gitignores] if it exists
+    ///
+    /// # Arguments
+    ///
+    /// * `gitignores` - Loaded ignore files
+    /// * `root` - Directory the patterns are relative to
+    /// * `path` - Path to the ignore file
+    ///
+    fn add_gitignore(
+        gitignores: &mut Vec<Arc<Gitignore>>,
+        root: &Path,
+        path: &Path,
+    ) -> io::Result<()> {
+        if !path.is_file() {
+            return Ok(());
+        }
+
+        let gitignore = Gitignore::load(root, path)?;
+        if !gitignore.is_empty() {
+            gitignores.push(Arc::new(gitignore));
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Convert a walked [path] (prefixed by the base directory) into an absolute path
+    ///
+    fn absolute_path(&self, base_dir: &Path, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.base_dir) {
+            Ok(relative) if relative.as_os_str().is_empty() => base_dir.to_path_buf(),
+            Ok(relative) => base_dir.join(relative),
+            Err(_) => path.to_path_buf(),
         }
     }
+
+    ///
+    /// Find the global excludes file, `core.excludesFile` from the git config or
+    /// `$XDG_CONFIG_HOME/git/ignore` by default
+    ///
+    fn global_excludes_file(repo_root: &Path) -> Option<PathBuf> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(repo_root)
+            .args(["config", "--path", "--get", "core.excludesFile"])
+            .output();
+
+        if let Ok(output) = output {
+            let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+            if output.status.success() && !path.is_empty() {
+                return Some(PathBuf::from(path));
+            }
+        }
+
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(config_home.join("git").join("ignore"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ------------------------------------------------
+    // ---------------- Util Functions ----------------
+    // ------------------------------------------------
+
+    fn create_temp_tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = env::temp_dir().join(format!("y3_reader_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        root
+    }
+
+    fn walked_paths(root: &Path, reader: &Reader) -> Vec<String> {
+        let mut paths: Vec<String> = reader
+            .paths()
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().to_string())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    // ---------------------------------------------------
+    // ---------------- [load_gitignore] -----------------
+    // ---------------------------------------------------
+
+    #[test]
+    fn test_gitignore_semantics() {
+        let root = create_temp_tree(
+            "gitignore",
+            &[
+                (".git/info/exclude", "secret.txt\n"),
+                (".gitignore", "target/\n*.log\n!keep.log\n/generated.txt\n"),
+                ("keep.log", ""),
+                ("debug.log", ""),
+                ("secret.txt", ""),
+                ("generated.txt", ""),
+                ("src/generated.txt", ""),
+                ("src/target/out.txt", ""),
+                ("src/main.rs", ""),
+                ("src/.gitignore", "*.rs\n!main.rs\n"),
+                ("src/lib.rs", ""),
+                ("docs/target/keep.log", ""),
+                ("docs/.y3ignore", "*.md\n"),
+                ("docs/guide.md", ""),
+                ("docs/.gitignore", "!*.log\n"),
+                ("docs/debug.log", ""),
+            ],
+        );
+        let mut reader = Reader::new(&root);
+        reader.load_gitignore().unwrap();
+        reader.get_files(&root).unwrap();
+
+        let paths = walked_paths(&root, &reader);
+
+        // Same files are found when walking in parallel
+        let mut parallel_reader = Reader::new(&root);
+        parallel_reader.set_jobs(4);
+        parallel_reader.load_gitignore().unwrap();
+        parallel_reader.get_files(&root).unwrap();
+
+        assert_eq!(walked_paths(&root, &parallel_reader), paths);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                ".gitignore",
+                "docs/.gitignore",
+                "docs/.y3ignore",
+                "docs/debug.log",
+                "keep.log",
+                "src/.gitignore",
+                "src/generated.txt",
+                "src/main.rs",
+            ]
+        );
+    }
+
+    // ---------------------------------------------------
+    // ---------------- [for_each_file] ------------------
+    // ---------------------------------------------------
+
+    #[cfg(unix)]
+    #[test]
+    fn test_for_each_file_non_utf8_paths() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let root = create_temp_tree("non_utf8", &[("a.txt", "")]);
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        fs::write(root.join(name), "").unwrap();
+
+        let mut reader = Reader::new(&root);
+        reader.set_jobs(2);
+
+        let mut paths = Vec::new();
+        let count = reader
+            .for_each_file(&root, |path| paths.push(path))
+            .unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(count, 2);
+        assert!(paths.contains(&root.join(name)));
+    }
+
+    #[test]
+    fn test_walk_panic_wakes_workers() {
+        let state = Mutex::new(WalkState {
+            active: 1,
+            ..Default::default()
+        });
+        let ready = Condvar::new();
+
+        thread::scope(|scope| {
+            // Waits like an idle worker, for the directory being walked
+            let waiter = scope.spawn(|| {
+                let mut guard = state.lock().unwrap();
+                while guard.active > 0 {
+                    guard = ready.wait(guard).unwrap();
+                }
+                guard.error.is_some()
+            });
+
+            let walker = scope.spawn(|| {
+                let _active = ActiveDir {
+                    state: &state,
+                    ready: &ready,
+                };
+                panic!("walking failed");
+            });
+
+            assert!(walker.join().is_err());
+            assert!(waiter.join().unwrap());
+        });
+    }
+
+    // ---------------------------------------------------
+    // ----------------- [Symlinks] ----------------------
+    // ---------------------------------------------------
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let root = create_temp_tree("symlinks", &[("docs/a.txt", ""), ("b.txt", "")]);
+        symlink(&root, root.join("docs/loop")).unwrap();
+        symlink(root.join("docs"), root.join("docs_link")).unwrap();
+        symlink(root.join("b.txt"), root.join("c.txt")).unwrap();
+        symlink(root.join("missing.txt"), root.join("broken.txt")).unwrap();
+
+        // Links into the walked directory are skipped, symlinked directories
+        // aren't walked by default
+        let mut reader = Reader::new(&root);
+        reader.get_files(&root).unwrap();
+        let default_paths = walked_paths(&root, &reader);
+
+        // Cycles are walked once, and each file is found under its own path, no
+        // matter which link the threads reach first
+        let followed_paths: Vec<Vec<String>> = (0..8)
+            .map(|_| {
+                let mut reader = Reader::new(&root);
+                reader.set_follow_symlinks(true);
+                reader.set_jobs(4);
+                reader.get_files(&root).unwrap();
+                walked_paths(&root, &reader)
+            })
+            .collect();
+
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(default_paths, vec!["b.txt", "docs/a.txt"]);
+
+        for paths in followed_paths {
+            assert_eq!(paths, vec!["b.txt", "docs/a.txt"]);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_outside() {
+        use std::os::unix::fs::symlink;
+
+        let outside = create_temp_tree("symlinks_outside", &[("lib/d.txt", ""), ("e.txt", "")]);
+        let root = create_temp_tree("symlinks_walked", &[("a.txt", "")]);
+        symlink(outside.join("e.txt"), root.join("e1.txt")).unwrap();
+        symlink(outside.join("e.txt"), root.join("e2.txt")).unwrap();
+        symlink(outside.join("lib"), root.join("lib1")).unwrap();
+        symlink(outside.join("lib"), root.join("lib2")).unwrap();
+        symlink(&outside, outside.join("lib/up")).unwrap();
+
+        let mut reader = Reader::new(&root);
+        reader.get_files(&root).unwrap();
+        let default_paths = walked_paths(&root, &reader);
+
+        // Whatever is reached through several links is found once, through the
+        // first link walked
+        let followed_paths: Vec<Vec<String>> = (0..8)
+            .map(|_| {
+                let mut reader = Reader::new(&root);
+                reader.set_follow_symlinks(true);
+                reader.set_jobs(4);
+                reader.get_files(&root).unwrap();
+                walked_paths(&root, &reader)
+            })
+            .collect();
+
+        fs::remove_dir_all(&root).unwrap();
+        fs::remove_dir_all(&outside).unwrap();
+
+        let is_e = |path: &String| path == "e1.txt" || path == "e2.txt";
+        let is_d = |path: &String| path.ends_with("/d.txt");
+
+        assert_eq!(default_paths.len(), 2);
+        assert_eq!(default_paths[0], "a.txt");
+        assert!(is_e(&default_paths[1]));
+
+        for paths in followed_paths {
+            assert_eq!(paths.len(), 3, "{paths:?}");
+            assert_eq!(paths.iter().filter(|path| is_e(path)).count(), 1);
+            assert_eq!(paths.iter().filter(|path| is_d(path)).count(), 1);
+        }
+    }
+
+    // ---------------------------------------------------
+    // -------------- [add_include_globs] ----------------
+    // ---------------------------------------------------
+
+    #[test]
+    fn test_include_exclude_globs() {
+        let root = create_temp_tree(
+            "include_exclude",
+            &[
+                ("README.md", ""),
+                ("docs/guide.md", ""),
+                ("vendor/lib/notes.md", ""),
+                ("src/main.rs", ""),
+                ("Cargo.lock", ""),
+                ("web/app.min.js", ""),
+                ("web/app.js", ""),
+            ],
+        );
+
+        let mut reader = Reader::new(&root);
+        reader.add_include_globs(&["*.md".to_string()]).unwrap();
+        reader.add_file_types(&["js"]).unwrap();
+        reader
+            .add_exclude_globs(&["vendor/**".to_string(), "*.min.js".to_string()])
+            .unwrap();
+        reader.get_files(&root).unwrap();
+
+        let paths = walked_paths(&root, &reader);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(paths, vec!["README.md", "docs/guide.md", "web/app.js"]);
+    }
 }